@@ -0,0 +1,118 @@
+use anchor_spl::token;
+use anyhow::{anyhow, Context, Result};
+use fairswap_sdk::{Deadline, PoolKeys, PoolState};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+
+use crate::PoolArgs;
+
+pub struct Client {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Client {
+    pub fn new(url: &str, keypair: &str) -> Result<Self> {
+        let path = expand_home(keypair);
+        let payer = read_keypair_file(&path)
+            .map_err(|e| anyhow!("failed to read keypair {}: {}", path, e))?;
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+        })
+    }
+
+    pub fn send(&self, ixs: &[Instruction]) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+
+    // None for accounts that do not exist
+    pub fn accounts(&self, keys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(self.rpc.get_multiple_accounts(keys)?)
+    }
+
+    pub fn clock(&self) -> Result<Clock> {
+        let account = self.rpc.get_account(&sysvar::clock::ID)?;
+        from_account(&account).context("failed to decode the clock sysvar")
+    }
+
+    pub fn slot_deadline(&self, slots: u64) -> Result<Deadline> {
+        Ok(Deadline::Slot(self.rpc.get_slot()?.saturating_add(slots)))
+    }
+
+    // Addresses of the pool, with each token program read from the owner of its mint.
+    // The LP mint only exists after the first deposit, which creates it under SPL Token.
+    pub fn pool_keys(&self, pool: &PoolArgs) -> Result<PoolKeys> {
+        let keys = PoolKeys::new(pool.mint_x, pool.mint_y, pool.seed);
+        let accounts = self.accounts(&[keys.mint_x, keys.mint_y, keys.mint_lp])?;
+        let owner = |account: &Option<Account>, name: &str| {
+            account
+                .as_ref()
+                .map(|account| account.owner)
+                .ok_or_else(|| anyhow!("{} does not exist", name))
+        };
+        Ok(PoolKeys::with_token_programs(
+            pool.mint_x,
+            pool.mint_y,
+            pool.seed,
+            owner(&accounts[0], "mint x")?,
+            owner(&accounts[1], "mint y")?,
+            owner(&accounts[2], "mint lp").unwrap_or(token::ID),
+        ))
+    }
+
+    pub fn pool_state(&self, keys: &PoolKeys) -> Result<PoolState> {
+        let accounts = self.accounts(&[
+            keys.config,
+            keys.pooldata,
+            keys.vault_x,
+            keys.vault_y,
+            keys.mint_lp,
+            keys.mint_x,
+            keys.mint_y,
+        ])?;
+        let names = [
+            "config", "pooldata", "vault x", "vault y", "mint lp", "mint x", "mint y",
+        ];
+        let accounts = accounts
+            .into_iter()
+            .zip(names)
+            .map(|(account, name)| {
+                account.ok_or_else(|| anyhow!("pool account {} does not exist", name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        PoolState::decode(
+            &accounts[0].data,
+            &accounts[1].data,
+            &accounts[2].data,
+            &accounts[3].data,
+            &accounts[4].data,
+            (&accounts[5].owner, &accounts[5].data),
+            (&accounts[6].owner, &accounts[6].data),
+        )
+        .map_err(|e| anyhow!("failed to decode the pool: {}", e))
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use fairswap_sdk::math::weighted;
+use fairswap_sdk::state::{
+    decode_config, decode_mint_supply, decode_pooldata, decode_token_amount,
+};
+use fairswap_sdk::{Config, CurveType, PoolData, SurplusPolicy};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+use crate::client::Client;
+use crate::{Command, Policy, PoolArgs};
+
+pub fn run(client: &Client, command: Command) -> Result<()> {
+    let payer = client.payer.pubkey();
+    match command {
+        Command::InitPool {
+            pool,
+            fee,
+            protocol_fee_bps,
+            window_slots,
+            treasury,
+            surplus_policy,
+            beneficiary,
+            stable_amp,
+            weight_x,
+        } => {
+            let keys = client.pool_keys(&pool)?;
+            let surplus_policy = match surplus_policy {
+                Policy::Lp => SurplusPolicy::Lp,
+                Policy::Treasury => SurplusPolicy::Treasury,
+                Policy::Beneficiary => SurplusPolicy::Beneficiary(
+                    beneficiary.context("--beneficiary is required for this policy")?,
+                ),
+            };
+            let (curve, amp, weights) = match (stable_amp, weight_x) {
+                (Some(amp), _) => (CurveType::StableSwap, amp, (50, 50)),
+                (None, Some(weight_x)) => {
+                    ensure!(
+                        weighted::is_valid_weights(weight_x, 100 - weight_x),
+                        "--weight-x must be one of {:?}",
+                        weighted::SUPPORTED_WEIGHTS
+                    );
+                    (CurveType::Weighted, 0, (weight_x, 100 - weight_x))
+                }
+                (None, None) => (CurveType::ConstantProduct, 0, (50, 50)),
+            };
+            let ix = keys.initialize_with_curve(
+                &payer,
+                fee,
+                protocol_fee_bps,
+                window_slots,
+                treasury.unwrap_or(payer),
+                surplus_policy,
+                curve,
+                amp,
+                weights.0,
+                weights.1,
+            );
+            println!("Pool {}", keys.config);
+            confirm(client, &[ix])
+        }
+        Command::Deposit {
+            pool,
+            amount,
+            max_x,
+            max_y,
+            deadline_slots,
+        } => {
+            let keys = client.pool_keys(&pool)?;
+            let deadline = client.slot_deadline(deadline_slots)?;
+            confirm(
+                client,
+                &[keys.deposit(&payer, amount, max_x, max_y, deadline)],
+            )
+        }
+        Command::Withdraw {
+            pool,
+            amount,
+            min_x,
+            min_y,
+            deadline_slots,
+        } => {
+            let keys = client.pool_keys(&pool)?;
+            let deadline = client.slot_deadline(deadline_slots)?;
+            confirm(
+                client,
+                &[keys.withdraw(&payer, amount, min_x, min_y, deadline)],
+            )
+        }
+        Command::Swap {
+            pool,
+            mint_in,
+            amount_in,
+            min_out,
+            slippage_bps,
+            deadline_slots,
+        } => {
+            let keys = client.pool_keys(&pool)?;
+            let min_out = match min_out {
+                Some(min_out) => min_out,
+                None => {
+                    ensure!(slippage_bps <= 10_000, "--slippage-bps is above 10000");
+                    let quote = client
+                        .pool_state(&keys)?
+                        .quote_swap(mint_in, amount_in, 1, &client.clock()?)
+                        .map_err(|e| anyhow!("quote failed: {}", e))?;
+                    let min_out =
+                        quote.amount_out as u128 * (10_000 - slippage_bps) as u128 / 10_000;
+                    println!(
+                        "Quoted {} out ({} before the fairness clamp), minimum {}",
+                        quote.amount_out, quote.amount_out_raw, min_out
+                    );
+                    (min_out as u64).max(1)
+                }
+            };
+            let deadline = client.slot_deadline(deadline_slots)?;
+            confirm(
+                client,
+                &[keys.swap(&payer, mint_in, amount_in, min_out, deadline)],
+            )
+        }
+        Command::Lock { pool } => {
+            let keys = client.pool_keys(&pool)?;
+            confirm(client, &[keys.lock(&payer)])
+        }
+        Command::Unlock { pool } => {
+            let keys = client.pool_keys(&pool)?;
+            confirm(client, &[keys.unlock(&payer)])
+        }
+        Command::ShowPool { pool } => show_pool(client, &pool),
+    }
+}
+
+fn confirm(client: &Client, ixs: &[Instruction]) -> Result<()> {
+    println!("Signature {}", client.send(ixs)?);
+    Ok(())
+}
+
+fn show_pool(client: &Client, pool: &PoolArgs) -> Result<()> {
+    let keys = client.pool_keys(pool)?;
+    let accounts = client.accounts(&[
+        keys.config,
+        keys.pooldata,
+        keys.vault_x,
+        keys.vault_y,
+        keys.mint_lp,
+    ])?;
+    let data = |index: usize, name: &str| {
+        accounts[index]
+            .as_ref()
+            .map(|account| account.data.as_slice())
+            .ok_or_else(|| anyhow!("pool {} has no {} account", keys.config, name))
+    };
+    let decode_err = |e| anyhow!("failed to decode the pool: {}", e);
+    let config = decode_config(data(0, "config")?).map_err(decode_err)?;
+    let pooldata = decode_pooldata(data(1, "pooldata")?).map_err(decode_err)?;
+    let vault_x = decode_token_amount(data(2, "vault x")?).map_err(decode_err)?;
+    let vault_y = decode_token_amount(data(3, "vault y")?).map_err(decode_err)?;
+    // the LP mint is created by the first deposit
+    let lp_supply = match accounts[4] {
+        Some(ref account) => decode_mint_supply(&account.data).map_err(decode_err)?,
+        None => 0,
+    };
+    let (reserve_x, reserve_y) = pooldata.reserves(vault_x, vault_y).map_err(decode_err)?;
+
+    print_config(&keys.config, &config);
+    println!("Reserves         {} x, {} y", reserve_x, reserve_y);
+    println!("Vaults           {} x, {} y", vault_x, vault_y);
+    println!(
+        "Pending surplus  {} x, {} y",
+        pooldata.pending_surplus_x, pooldata.pending_surplus_y
+    );
+    println!(
+        "Protocol fees    {} x, {} y",
+        pooldata.protocol_fees_x, pooldata.protocol_fees_y
+    );
+    println!("LP supply        {}", lp_supply);
+    print_window(&pooldata, client.rpc.get_slot()?);
+    Ok(())
+}
+
+fn print_config(address: &Pubkey, config: &Config) {
+    println!("Pool             {}", address);
+    println!("Seed             {}", config.seed);
+    println!("Mint x           {}", config.mint_x);
+    println!("Mint y           {}", config.mint_y);
+    match config.authority {
+        Some(authority) => println!("Authority        {}", authority),
+        None => println!("Authority        renounced"),
+    }
+    if let Some(pending) = config.pending_authority {
+        println!("Pending          {}", pending);
+    }
+    println!(
+        "Fee              {} bps, {} bps of it to the protocol",
+        config.fee, config.protocol_fee_bps
+    );
+    println!("Treasury         {}", config.treasury);
+    let policy = match config.surplus_policy {
+        SurplusPolicy::Lp => "LPs".to_string(),
+        SurplusPolicy::Treasury => "treasury".to_string(),
+        SurplusPolicy::Beneficiary(beneficiary) => format!("beneficiary {}", beneficiary),
+    };
+    println!("Surplus to       {}", policy);
+    match config.curve {
+        CurveType::ConstantProduct => println!("Curve            constant product"),
+        CurveType::StableSwap if config.amp.initial_amp == config.amp.target_amp => {
+            println!("Curve            stable, amp {}", config.amp.target_amp)
+        }
+        CurveType::StableSwap => println!(
+            "Curve            stable, amp {} to {} from {} to {}",
+            config.amp.initial_amp, config.amp.target_amp, config.amp.start_ts, config.amp.end_ts
+        ),
+        CurveType::Weighted => println!(
+            "Curve            weighted, {}% x / {}% y",
+            config.weight_x, config.weight_y
+        ),
+    }
+    println!("Locked           {}", config.locked);
+}
+
+fn print_window(pooldata: &PoolData, current_slot: u64) {
+    let status = match pooldata.is_window_expired(current_slot) {
+        true => "expired",
+        false => "open",
+    };
+    println!(
+        "Window           {} slots from slot {}, {} at slot {}",
+        pooldata.window_slots, pooldata.last_slot, status, current_slot
+    );
+    // ratios are deposit / withdraw in raw units, so a higher mark is a worse price
+    let high = |high: Option<u128>| match high {
+        Some(ratio) => format_ratio(ratio, pooldata.precision),
+        None => "none".to_string(),
+    };
+    println!("Buying x high    {} y per x", high(pooldata.buying_x_high));
+    println!("Buying y high    {} x per y", high(pooldata.buying_y_high));
+    println!(
+        "Surplus total    {} x, {} y",
+        pooldata.surplus_x, pooldata.surplus_y
+    );
+}
+
+fn format_ratio(ratio: u128, precision: u8) -> String {
+    let scale = 10u128.pow(precision as u32);
+    match precision {
+        0 => ratio.to_string(),
+        _ => format!(
+            "{}.{:0width$}",
+            ratio / scale,
+            ratio % scale,
+            width = precision as usize
+        ),
+    }
+}
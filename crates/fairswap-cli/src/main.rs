@@ -0,0 +1,182 @@
+// Admin and trader commands for fairswap pools, signed with a local keypair file.
+// Amounts are raw token units, e.g. 1_000_000 is one token of a 6 decimal mint.
+mod client;
+mod commands;
+
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use solana_sdk::pubkey::Pubkey;
+
+use client::Client;
+
+#[derive(Parser)]
+#[command(
+    name = "fairswap-cli",
+    version,
+    about = "Manage and trade on fairswap pools"
+)]
+struct Cli {
+    /// RPC endpoint of the cluster, a local test validator by default
+    #[arg(short, long, global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// Keypair file that pays for and signs every transaction
+    #[arg(short, long, global = true, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+// Identifies a pool, the same two mints can back several pools with different seeds
+#[derive(Args)]
+pub struct PoolArgs {
+    /// First mint of the pair, in the order the pool was created with
+    #[arg(long)]
+    pub mint_x: Pubkey,
+
+    /// Second mint of the pair
+    #[arg(long)]
+    pub mint_y: Pubkey,
+
+    /// Seed the pool was created with
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Policy {
+    Lp,
+    Treasury,
+    Beneficiary,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a pool, the keypair becomes its authority
+    InitPool {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// Swap fee in basis points of the input
+        #[arg(long)]
+        fee: u16,
+
+        /// Share of the fee kept for the treasury, in basis points of the fee
+        #[arg(long, default_value_t = 0)]
+        protocol_fee_bps: u16,
+
+        /// Slots the fairness high-water marks are kept for
+        #[arg(long)]
+        window_slots: u64,
+
+        /// Receives protocol fees, the keypair by default
+        #[arg(long)]
+        treasury: Option<Pubkey>,
+
+        /// Who the output withheld by the fairness clamp goes to
+        #[arg(long, value_enum, default_value_t = Policy::Lp)]
+        surplus_policy: Policy,
+
+        /// Recipient for `--surplus-policy beneficiary`
+        #[arg(long, required_if_eq("surplus_policy", "beneficiary"))]
+        beneficiary: Option<Pubkey>,
+
+        /// Trade on the StableSwap curve with this amplification instead of x * y = k
+        #[arg(long)]
+        stable_amp: Option<u64>,
+
+        /// Make the pool weighted with this percent of its value in x (20, 40, 60 or 80), the rest in y
+        #[arg(long, conflicts_with = "stable_amp")]
+        weight_x: Option<u8>,
+    },
+
+    /// Add liquidity, the first deposit sets the price and mints sqrt(x * y) LP
+    Deposit {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// LP tokens to mint, only a lower bound for the first deposit
+        #[arg(long)]
+        amount: u64,
+
+        #[arg(long)]
+        max_x: u64,
+
+        #[arg(long)]
+        max_y: u64,
+
+        /// Slots from now after which the transaction is rejected
+        #[arg(long, default_value_t = 150)]
+        deadline_slots: u64,
+    },
+
+    /// Burn LP tokens for their share of both vaults
+    Withdraw {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// LP tokens to burn
+        #[arg(long)]
+        amount: u64,
+
+        #[arg(long)]
+        min_x: u64,
+
+        #[arg(long)]
+        min_y: u64,
+
+        /// Slots from now after which the transaction is rejected
+        #[arg(long, default_value_t = 150)]
+        deadline_slots: u64,
+    },
+
+    /// Swap an exact input, the minimum output is quoted off-chain unless given
+    Swap {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// Mint of the token paid in
+        #[arg(long)]
+        mint_in: Pubkey,
+
+        #[arg(long)]
+        amount_in: u64,
+
+        /// Smallest acceptable output, overrides --slippage-bps
+        #[arg(long)]
+        min_out: Option<u64>,
+
+        /// Tolerance below the quoted output, in basis points
+        #[arg(long, default_value_t = 50)]
+        slippage_bps: u16,
+
+        /// Slots from now after which the transaction is rejected
+        #[arg(long, default_value_t = 150)]
+        deadline_slots: u64,
+    },
+
+    /// Stop all deposits, withdrawals and swaps
+    Lock {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+
+    /// Resume a locked pool
+    Unlock {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+
+    /// Print the pool's settings, reserves and fairness window
+    ShowPool {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let client = Client::new(&cli.url, &cli.keypair)?;
+    commands::run(&client, cli.command)
+}
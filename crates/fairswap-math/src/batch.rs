@@ -0,0 +1,116 @@
+// Uniform-price clearing of a batch of swaps against a constant product pool. Both sides
+// trade at (reserve_y + in_y) / (reserve_x + in_x) Y per X, the ratio of the reserves once
+// every input has landed. Only the imbalance between the sides crosses the curve, and at
+// that price the pool's net trade keeps x * y exactly, so the order of the swaps inside
+// the batch makes no difference to anyone.
+use crate::{mul_div, to_u64, MathError, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clearing {
+    // X paid out to the side that deposited Y
+    pub out_x: u64,
+    // Y paid out to the side that deposited X
+    pub out_y: u64,
+}
+
+// Totals paid to each side for in_x and in_y, rounded down so x * y never shrinks.
+pub fn clear(reserve_x: u64, reserve_y: u64, in_x: u64, in_y: u64) -> Result<Clearing> {
+    if reserve_x == 0 || reserve_y == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    let total_x = reserve_x as u128 + in_x as u128;
+    let total_y = reserve_y as u128 + in_y as u128;
+    Ok(Clearing {
+        out_x: to_u64(mul_div(in_y as u128, total_x, total_y, false)?)?,
+        out_y: to_u64(mul_div(in_x as u128, total_y, total_x, false)?)?,
+    })
+}
+
+// One order's share of a side's payout, rounded down so the shares never exceed total_out.
+pub fn pro_rata(amount_in: u64, total_in: u64, total_out: u64) -> Result<u64> {
+    if amount_in > total_in {
+        return Err(MathError::InsufficientBalance);
+    }
+    to_u64(mul_div(
+        amount_in as u128,
+        total_out as u128,
+        total_in as u128,
+        false,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::swap_exact_in;
+
+    #[test]
+    fn clearing_never_shrinks_k() {
+        for reserve_x in 1..16u64 {
+            for reserve_y in 1..16u64 {
+                for in_x in 0..24u64 {
+                    for in_y in 0..24u64 {
+                        let c = clear(reserve_x, reserve_y, in_x, in_y).unwrap();
+                        let x_after = (reserve_x + in_x - c.out_x) as u128;
+                        let y_after = (reserve_y + in_y - c.out_y) as u128;
+                        assert!(x_after * y_after >= reserve_x as u128 * reserve_y as u128);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn both_sides_get_the_same_price() {
+        let c = clear(1_000_000, 2_000_000, 10_000, 30_000).unwrap();
+        // 2_030_000 / 1_010_000 Y per X for both sides
+        assert_eq!(c.out_y, 10_000 * 2_030_000 / 1_010_000);
+        assert_eq!(c.out_x, 30_000 * 1_010_000 / 2_030_000);
+        // the matched part never reaches the curve, so both sides beat trading alone
+        let alone_x = swap_exact_in(1_000_000, 2_000_000, 10_000, 0).unwrap();
+        let alone_y = swap_exact_in(2_000_000, 1_000_000, 30_000, 0).unwrap();
+        assert!(c.out_y >= alone_x.withdraw);
+        assert!(c.out_x >= alone_y.withdraw);
+    }
+
+    #[test]
+    fn one_sided_batch_matches_the_curve() {
+        for reserve_x in 1..16u64 {
+            for reserve_y in 1..16u64 {
+                for in_x in 0..24u64 {
+                    let c = clear(reserve_x, reserve_y, in_x, 0).unwrap();
+                    let res = swap_exact_in(reserve_x, reserve_y, in_x, 0).unwrap();
+                    assert_eq!(c.out_y, res.withdraw);
+                    assert_eq!(c.out_x, 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clear_errors() {
+        assert_eq!(clear(0, 1, 1, 1), Err(MathError::ZeroBalance));
+        assert_eq!(clear(1, 0, 1, 1), Err(MathError::ZeroBalance));
+        // a vault never holds more than a u64 supply, so reserve plus input fits one
+        let half = u64::MAX / 2;
+        let c = clear(half, half, half, half).unwrap();
+        assert_eq!((c.out_x, c.out_y), (half, half));
+    }
+
+    #[test]
+    fn pro_rata_shares_fit_the_total() {
+        let amounts = [1u64, 7, 13, 29, 50];
+        let total_in: u64 = amounts.iter().sum();
+        for total_out in 0..200u64 {
+            let paid: u64 = amounts
+                .iter()
+                .map(|amount| pro_rata(*amount, total_in, total_out).unwrap())
+                .sum();
+            assert!(paid <= total_out);
+            // each share loses less than one unit to rounding
+            assert!(total_out - paid < amounts.len() as u64);
+        }
+        assert_eq!(pro_rata(2, 1, 10), Err(MathError::InsufficientBalance));
+        assert_eq!(pro_rata(0, 0, 10), Err(MathError::ZeroBalance));
+    }
+}
@@ -0,0 +1,290 @@
+// Constant product curve x * y = k with a fee in basis points of the input.
+use crate::{mul_div, to_u64, MathError, Result};
+
+pub const FEE_DENOMINATOR: u16 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapResult {
+    // full input, fee included
+    pub deposit: u64,
+    pub withdraw: u64,
+    // part of deposit kept as fee, in the input token
+    pub fee: u64,
+}
+
+// Fee in basis points charged on amount, rounded up.
+pub fn fee_amount(amount: u64, fee: u16) -> Result<u64> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+    to_u64(mul_div(
+        amount as u128,
+        fee as u128,
+        FEE_DENOMINATOR as u128,
+        true,
+    )?)
+}
+
+// Output for amount_in. The fee is rounded up and the output down, so k never shrinks.
+pub fn swap_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<SwapResult> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    let fee_amount = fee_amount(amount_in, fee)?;
+    let k = reserve_in as u128 * reserve_out as u128;
+    let reserve_in_after = reserve_in as u128 + (amount_in - fee_amount) as u128;
+    // the reserve left behind rounds up, which rounds the output down
+    let reserve_out_after = mul_div(k, 1, reserve_in_after, true)?;
+    Ok(SwapResult {
+        deposit: amount_in,
+        withdraw: to_u64(reserve_out as u128 - reserve_out_after)?,
+        fee: fee_amount,
+    })
+}
+
+// Smallest input for which swap_exact_in releases at least amount_out, rounded up.
+pub fn swap_amount_in(reserve_in: u64, reserve_out: u64, amount_out: u64, fee: u16) -> Result<u64> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    if amount_out >= reserve_out {
+        return Err(MathError::InsufficientBalance);
+    }
+    let k = reserve_in as u128 * reserve_out as u128;
+    let reserve_in_after = mul_div(k, 1, (reserve_out - amount_out) as u128, true)?;
+    let amount_in_after_fee = reserve_in_after - reserve_in as u128;
+    let amount_in = mul_div(
+        amount_in_after_fee,
+        FEE_DENOMINATOR as u128,
+        (FEE_DENOMINATOR - fee) as u128,
+        true,
+    )?;
+    to_u64(amount_in)
+}
+
+// Tokens needed to mint amount LP against the reserves, rounded up.
+pub fn deposit_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    amount: u64,
+) -> Result<(u64, u64)> {
+    let x = mul_div(reserve_x as u128, amount as u128, supply as u128, true)?;
+    let y = mul_div(reserve_y as u128, amount as u128, supply as u128, true)?;
+    Ok((to_u64(x)?, to_u64(y)?))
+}
+
+// Tokens released by burning amount LP, rounded down.
+pub fn withdraw_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    amount: u64,
+) -> Result<(u64, u64)> {
+    if amount > supply {
+        return Err(MathError::InsufficientBalance);
+    }
+    let x = mul_div(reserve_x as u128, amount as u128, supply as u128, false)?;
+    let y = mul_div(reserve_y as u128, amount as u128, supply as u128, false)?;
+    Ok((to_u64(x)?, to_u64(y)?))
+}
+
+// LP supply for a pool's first deposit, sqrt(x * y) rounded down.
+pub fn initial_liquidity(x: u64, y: u64) -> u64 {
+    // the root of a product of two u64 always fits a u64
+    isqrt(x as u128 * y as u128) as u64
+}
+
+// Integer square root by Newton's method, rounded down
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut root = value;
+    let mut next = value.div_ceil(2);
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEES: [u16; 6] = [0, 1, 30, 100, 2_500, 9_999];
+
+    #[test]
+    fn swap_exact_in_pays_the_largest_output_keeping_k() {
+        for reserve_in in 1..24u64 {
+            for reserve_out in 1..24u64 {
+                for amount_in in 0..48u64 {
+                    for fee in FEES {
+                        let res = swap_exact_in(reserve_in, reserve_out, amount_in, fee).unwrap();
+                        let fee_amount = (amount_in * fee as u64).div_ceil(10_000);
+                        assert_eq!(res.fee, fee_amount);
+                        assert_eq!(res.deposit, amount_in);
+
+                        let k = reserve_in as u128 * reserve_out as u128;
+                        let reserve_in_after = (reserve_in + amount_in - fee_amount) as u128;
+                        let reserve_out_after = (reserve_out - res.withdraw) as u128;
+                        assert!(reserve_in_after * reserve_out_after >= k);
+                        // one more unit out would break the invariant
+                        if res.withdraw < reserve_out {
+                            assert!(reserve_in_after * (reserve_out_after - 1) < k);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fee_amount_rounds_up() {
+        assert_eq!(fee_amount(0, 30), Ok(0));
+        assert_eq!(fee_amount(1, 30), Ok(1));
+        assert_eq!(fee_amount(10_000, 30), Ok(30));
+        assert_eq!(fee_amount(10_001, 30), Ok(31));
+        assert_eq!(
+            fee_amount(u64::MAX, 9_999),
+            Ok(u64::MAX - u64::MAX / 10_000)
+        );
+        assert_eq!(fee_amount(1, 10_000), Err(MathError::InvalidFee));
+    }
+
+    #[test]
+    fn swap_exact_in_examples() {
+        let res = swap_exact_in(2_000_000, 2_000_000, 20_000, 0).unwrap();
+        assert_eq!(res.withdraw, 19_801);
+        assert_eq!(res.fee, 0);
+
+        // 1% of 1e6 is charged
+        let res = swap_exact_in(100_000_000, 100_000_000, 1_000_000, 100).unwrap();
+        assert_eq!(res.fee, 10_000);
+        assert_eq!(res.withdraw, 980_295);
+    }
+
+    #[test]
+    fn swap_exact_in_errors() {
+        assert_eq!(swap_exact_in(0, 10, 1, 0), Err(MathError::ZeroBalance));
+        assert_eq!(swap_exact_in(10, 0, 1, 0), Err(MathError::ZeroBalance));
+        assert_eq!(swap_exact_in(10, 10, 1, 10_000), Err(MathError::InvalidFee));
+        let res = swap_exact_in(u64::MAX, u64::MAX, u64::MAX, 0).unwrap();
+        assert_eq!(res.withdraw, u64::MAX / 2);
+    }
+
+    #[test]
+    fn swap_amount_in_is_the_smallest_sufficient_input() {
+        for reserve_in in 1..24u64 {
+            for reserve_out in 2..24u64 {
+                for amount_out in 0..reserve_out {
+                    for fee in FEES {
+                        let amount_in =
+                            swap_amount_in(reserve_in, reserve_out, amount_out, fee).unwrap();
+                        let res = swap_exact_in(reserve_in, reserve_out, amount_in, fee).unwrap();
+                        assert!(res.withdraw >= amount_out);
+                        if amount_in > 0 {
+                            let less =
+                                swap_exact_in(reserve_in, reserve_out, amount_in - 1, fee).unwrap();
+                            assert!(less.withdraw < amount_out);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_amount_in_errors() {
+        assert_eq!(
+            swap_amount_in(10, 10, 10, 0),
+            Err(MathError::InsufficientBalance)
+        );
+        assert_eq!(
+            swap_amount_in(10, 10, 1, 10_000),
+            Err(MathError::InvalidFee)
+        );
+        assert_eq!(swap_amount_in(0, 10, 1, 0), Err(MathError::ZeroBalance));
+        assert_eq!(
+            swap_amount_in(u64::MAX, u64::MAX, u64::MAX - 1, 0),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn deposit_rounds_up_and_withdraw_rounds_down() {
+        for reserve_x in 0..20u64 {
+            for reserve_y in 0..20u64 {
+                for supply in 1..20u64 {
+                    for amount in 0..=supply {
+                        let (dx, dy) =
+                            deposit_amounts(reserve_x, reserve_y, supply, amount).unwrap();
+                        let (wx, wy) =
+                            withdraw_amounts(reserve_x, reserve_y, supply, amount).unwrap();
+                        for (deposit, withdraw, reserve) in
+                            [(dx, wx, reserve_x), (dy, wy, reserve_y)]
+                        {
+                            let exact = reserve * amount;
+                            assert!(
+                                deposit * supply >= exact
+                                    && (deposit == 0 || (deposit - 1) * supply < exact)
+                            );
+                            assert!(withdraw * supply <= exact && exact < (withdraw + 1) * supply);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn deposit_and_withdraw_errors() {
+        assert_eq!(deposit_amounts(1, 1, 0, 1), Err(MathError::ZeroBalance));
+        assert_eq!(withdraw_amounts(1, 1, 0, 0), Err(MathError::ZeroBalance));
+        assert_eq!(
+            withdraw_amounts(1, 1, 1, 2),
+            Err(MathError::InsufficientBalance)
+        );
+        assert_eq!(deposit_amounts(u64::MAX, 1, 1, 2), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn isqrt_rounds_down() {
+        for value in 0..100_000u128 {
+            let root = isqrt(value);
+            assert!(root * root <= value && (root + 1) * (root + 1) > value);
+        }
+        for value in [
+            u64::MAX as u128,
+            u128::MAX,
+            (u64::MAX as u128) * (u64::MAX as u128),
+        ] {
+            let root = isqrt(value);
+            assert!(root * root <= value);
+            // (root + 1)^2 either overflows or exceeds value
+            if let Some(square) = (root + 1).checked_mul(root + 1) {
+                assert!(square > value);
+            }
+        }
+    }
+
+    #[test]
+    fn initial_liquidity_examples() {
+        assert_eq!(initial_liquidity(2_000_000, 2_000_000), 2_000_000);
+        assert_eq!(initial_liquidity(u64::MAX, u64::MAX), u64::MAX);
+        assert_eq!(initial_liquidity(0, 5), 0);
+        assert_eq!(initial_liquidity(2, 3), 2);
+    }
+}
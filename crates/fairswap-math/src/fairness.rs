@@ -0,0 +1,201 @@
+// Execution ratios and the high-water marks fairswap clamps them to. A ratio is
+// amount1 / amount2 scaled by 10^precision, for a swap that is deposit / withdraw,
+// so a higher ratio is a worse price for the trader.
+use crate::{mul_div, to_u64, MathError, Result};
+
+pub fn scale(precision: u8) -> Result<u128> {
+    10u128
+        .checked_pow(precision as u32)
+        .ok_or(MathError::InvalidPrecision)
+}
+
+// LP tokens sit between the two sides, matching sqrt(x * y) in raw units
+pub fn lp_decimals(decimals_x: u8, decimals_y: u8) -> u8 {
+    ((decimals_x as u16 + decimals_y as u16) / 2) as u8
+}
+
+// amount1 * 10^precision / amount2, rounded down
+pub fn limit_price(amount1: u64, amount2: u64, precision: u8) -> Result<u128> {
+    weighted_limit_price(amount1, 1, amount2, 1, precision)
+}
+
+// (amount1 / weight1) / (amount2 / weight2) scaled like limit_price, the spot ratio of a
+// weighted pool's reserves. Rounded down.
+pub fn weighted_limit_price(
+    amount1: u64,
+    weight1: u8,
+    amount2: u64,
+    weight2: u8,
+    precision: u8,
+) -> Result<u128> {
+    if weight1 == 0 || weight2 == 0 {
+        return Err(MathError::InvalidWeights);
+    }
+    mul_div(
+        amount1 as u128 * weight2 as u128,
+        scale(precision)?,
+        amount2 as u128 * weight1 as u128,
+        false,
+    )
+}
+
+// Input needed to receive amount_out at ratio, rounded up
+pub fn amount_in_at_ratio(amount_out: u64, ratio: u128, precision: u8) -> Result<u64> {
+    to_u64(mul_div(amount_out as u128, ratio, scale(precision)?, true)?)
+}
+
+// Output paid for amount_in at ratio, rounded down
+pub fn amount_out_at_ratio(amount_in: u64, ratio: u128, precision: u8) -> Result<u64> {
+    to_u64(mul_div(amount_in as u128, scale(precision)?, ratio, false)?)
+}
+
+// Clamps ratio to the high-water mark, or raises the mark to ratio if it is worse.
+// Returns the ratio the swap executes at.
+pub fn apply_high_water_mark(high: &mut Option<u128>, ratio: u128) -> u128 {
+    match *high {
+        Some(h) if ratio < h => h,
+        _ => {
+            *high = Some(ratio);
+            ratio
+        }
+    }
+}
+
+// Moves a high-water mark towards or away from spot so its distance from spot, the
+// price impact it encodes, scales by old_supply / new_supply. Rounded towards spot.
+pub fn rescale_high(high: u128, spot: u128, old_supply: u64, new_supply: u64) -> Result<u128> {
+    let impact = mul_div(
+        high.abs_diff(spot),
+        old_supply as u128,
+        new_supply as u128,
+        false,
+    )?;
+    match high >= spot {
+        true => spot.checked_add(impact).ok_or(MathError::Overflow),
+        false => Ok(spot.saturating_sub(impact)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_limits() {
+        assert_eq!(scale(0), Ok(1));
+        assert_eq!(scale(18), Ok(10u128.pow(18)));
+        assert_eq!(scale(38), Ok(10u128.pow(38)));
+        assert_eq!(scale(39), Err(MathError::InvalidPrecision));
+    }
+
+    #[test]
+    fn lp_decimals_examples() {
+        assert_eq!(lp_decimals(6, 6), 6);
+        assert_eq!(lp_decimals(6, 9), 7);
+        assert_eq!(lp_decimals(255, 255), 255);
+    }
+
+    #[test]
+    fn limit_price_rounds_down() {
+        for amount1 in 0..64u64 {
+            for amount2 in 1..64u64 {
+                for precision in 0..4u8 {
+                    let ratio = limit_price(amount1, amount2, precision).unwrap();
+                    let exact = amount1 as u128 * 10u128.pow(precision as u32);
+                    assert!(ratio * amount2 as u128 <= exact);
+                    assert!(exact < (ratio + 1) * amount2 as u128);
+                }
+            }
+        }
+        assert_eq!(limit_price(3, 2, 6), Ok(1_500_000));
+        assert_eq!(limit_price(2, 3, 6), Ok(666_666));
+        assert_eq!(limit_price(1, 0, 6), Err(MathError::ZeroBalance));
+        // u64 amounts at the maximum precision fit in a u128
+        assert!(limit_price(u64::MAX, 1, 18).is_ok());
+    }
+
+    #[test]
+    fn weighted_limit_price_divides_out_the_weights() {
+        assert_eq!(weighted_limit_price(4, 80, 1, 20, 6), Ok(1_000_000));
+        assert_eq!(weighted_limit_price(1, 20, 4, 80, 6), Ok(1_000_000));
+        assert_eq!(weighted_limit_price(3, 50, 2, 50, 6), limit_price(3, 2, 6));
+        assert_eq!(
+            weighted_limit_price(1, 0, 1, 100, 6),
+            Err(MathError::InvalidWeights)
+        );
+        assert!(weighted_limit_price(u64::MAX, 99, 1, 1, 18).is_ok());
+    }
+
+    #[test]
+    fn amounts_at_ratio_round_in_favour_of_the_pool() {
+        for amount in 0..64u64 {
+            for ratio in 1..64u128 {
+                for precision in 0..3u8 {
+                    let scale = 10u128.pow(precision as u32);
+                    let amount_in = amount_in_at_ratio(amount, ratio, precision).unwrap() as u128;
+                    assert!(amount_in * scale >= amount as u128 * ratio);
+                    assert!(amount_in == 0 || (amount_in - 1) * scale < amount as u128 * ratio);
+
+                    let amount_out = amount_out_at_ratio(amount, ratio, precision).unwrap() as u128;
+                    assert!(amount_out * ratio <= amount as u128 * scale);
+                    assert!(amount as u128 * scale < (amount_out + 1) * ratio);
+                }
+            }
+        }
+        assert_eq!(amount_out_at_ratio(1, 0, 6), Err(MathError::ZeroBalance));
+        assert_eq!(
+            amount_out_at_ratio(u64::MAX, 1, 6),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn ratio_round_trip_never_pays_more_than_the_curve() {
+        // a clamp-free swap re-priced at its own ratio pays at most the curve output
+        for deposit in 1..200u64 {
+            for withdraw in 1..200u64 {
+                let ratio = limit_price(deposit, withdraw, 6).unwrap();
+                assert!(amount_out_at_ratio(deposit, ratio, 6).unwrap() >= withdraw);
+                assert!(amount_out_at_ratio(deposit, ratio + 1, 6).unwrap() <= withdraw);
+            }
+        }
+    }
+
+    #[test]
+    fn high_water_mark() {
+        let mut high = None;
+        assert_eq!(apply_high_water_mark(&mut high, 10), 10);
+        assert_eq!(high, Some(10));
+        // a worse ratio raises the mark
+        assert_eq!(apply_high_water_mark(&mut high, 12), 12);
+        assert_eq!(high, Some(12));
+        // a better one is clamped to it
+        assert_eq!(apply_high_water_mark(&mut high, 11), 12);
+        assert_eq!(high, Some(12));
+        assert_eq!(apply_high_water_mark(&mut high, 12), 12);
+    }
+
+    #[test]
+    fn rescale_high_scales_the_distance_from_spot() {
+        for spot in 0..32u128 {
+            for high in 0..32u128 {
+                for old_supply in 1..8u64 {
+                    for new_supply in 1..8u64 {
+                        let rescaled = rescale_high(high, spot, old_supply, new_supply).unwrap();
+                        let distance =
+                            high.abs_diff(spot) * old_supply as u128 / new_supply as u128;
+                        let expected = match high >= spot {
+                            true => spot + distance,
+                            false => spot.saturating_sub(distance),
+                        };
+                        assert_eq!(rescaled, expected);
+                    }
+                }
+            }
+        }
+        // doubling the supply halves the impact
+        assert_eq!(rescale_high(1_100, 1_000, 1, 2), Ok(1_050));
+        assert_eq!(rescale_high(900, 1_000, 1, 2), Ok(950));
+        assert_eq!(rescale_high(1, 1, 1, 0), Err(MathError::ZeroBalance));
+    }
+}
@@ -0,0 +1,241 @@
+// Integer math behind fairswap and simple_amm. Every function states which way it rounds,
+// and the direction is always the one that favours the pool over the caller.
+#![no_std]
+
+pub mod batch;
+pub mod curve;
+pub mod fairness;
+pub mod stable;
+pub mod weighted;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    InvalidPrecision,
+    InvalidFee,
+    InvalidAmp,
+    InvalidWeights,
+    Overflow,
+    Underflow,
+    ZeroBalance,
+    InsufficientBalance,
+    // a weighted pool swap past the share of a reserve one trade may move
+    TradeTooLarge,
+    // an iterative solver ran out of rounds, only seen on absurdly unbalanced reserves
+    NotConverged,
+}
+
+pub type Result<T> = core::result::Result<T, MathError>;
+
+// a * b / c, rounded down or up. A product past u128 is carried in 256 bits, so only a
+// quotient that doesn't fit u128 overflows.
+pub fn mul_div(a: u128, b: u128, c: u128, round_up: bool) -> Result<u128> {
+    if c == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    let (quotient, remainder) = match a.checked_mul(b) {
+        Some(product) => (product / c, product % c),
+        None => div_wide(mul_wide(a, b), c)?,
+    };
+    match round_up && remainder != 0 {
+        true => quotient.checked_add(1).ok_or(MathError::Overflow),
+        false => Ok(quotient),
+    }
+}
+
+// Full 256-bit product of a and b as (high, low) halves
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & LOW);
+    let (b1, b0) = (b >> 64, b & LOW);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    // below 3 * 2^64, so it can't overflow
+    let middle = (p00 >> 64) + (p01 & LOW) + (p10 & LOW);
+    let low = (p00 & LOW) | (middle << 64);
+    let high = p11 + (p01 >> 64) + (p10 >> 64) + (middle >> 64);
+    (high, low)
+}
+
+// Schoolbook division of a 256-bit value by c, returning (quotient, remainder)
+fn div_wide((high, low): (u128, u128), c: u128) -> Result<(u128, u128)> {
+    if high >= c {
+        return Err(MathError::Overflow);
+    }
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        // remainder < c before the shift, so one subtraction brings it back below c
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Ok((quotient, remainder))
+}
+
+pub fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+// Invariant a pool trades on, with the amplification already resolved for StableSwap.
+// Weighted weights are oriented for the swap, weight_in belongs to the input token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    ConstantProduct,
+    StableSwap { amp: u64 },
+    Weighted { weight_in: u8, weight_out: u8 },
+}
+
+impl Curve {
+    // The same curve for a swap the other way round
+    pub fn reversed(self) -> Curve {
+        match self {
+            Curve::Weighted {
+                weight_in,
+                weight_out,
+            } => Curve::Weighted {
+                weight_in: weight_out,
+                weight_out: weight_in,
+            },
+            curve => curve,
+        }
+    }
+
+    pub fn swap_exact_in(
+        &self,
+        reserve_in: u64,
+        reserve_out: u64,
+        amount_in: u64,
+        fee: u16,
+    ) -> Result<curve::SwapResult> {
+        match *self {
+            Curve::ConstantProduct => curve::swap_exact_in(reserve_in, reserve_out, amount_in, fee),
+            Curve::StableSwap { amp } => {
+                stable::swap_exact_in(reserve_in, reserve_out, amount_in, fee, amp)
+            }
+            Curve::Weighted {
+                weight_in,
+                weight_out,
+            } => weighted::swap_exact_in(
+                reserve_in,
+                reserve_out,
+                amount_in,
+                fee,
+                weight_in,
+                weight_out,
+            ),
+        }
+    }
+
+    pub fn swap_amount_in(
+        &self,
+        reserve_in: u64,
+        reserve_out: u64,
+        amount_out: u64,
+        fee: u16,
+    ) -> Result<u64> {
+        match *self {
+            Curve::ConstantProduct => {
+                curve::swap_amount_in(reserve_in, reserve_out, amount_out, fee)
+            }
+            Curve::StableSwap { amp } => {
+                stable::swap_amount_in(reserve_in, reserve_out, amount_out, fee, amp)
+            }
+            Curve::Weighted {
+                weight_in,
+                weight_out,
+            } => weighted::swap_amount_in(
+                reserve_in,
+                reserve_out,
+                amount_out,
+                fee,
+                weight_in,
+                weight_out,
+            ),
+        }
+    }
+
+    // Marginal price of the output token in the input token, scaled by 10^precision like
+    // fairness::limit_price. Rounded down.
+    pub fn spot_ratio(&self, reserve_in: u64, reserve_out: u64, precision: u8) -> Result<u128> {
+        match *self {
+            Curve::ConstantProduct => fairness::limit_price(reserve_in, reserve_out, precision),
+            Curve::StableSwap { amp } => {
+                stable::spot_ratio(reserve_in, reserve_out, amp, precision)
+            }
+            Curve::Weighted {
+                weight_in,
+                weight_out,
+            } => weighted::spot_ratio(reserve_in, reserve_out, weight_in, weight_out, precision),
+        }
+    }
+
+    // LP supply for a pool's first deposit. Later deposits are proportional, so a
+    // weighted pool only needs some starting supply and takes sqrt(x * y) as well.
+    pub fn initial_liquidity(&self, x: u64, y: u64) -> Result<u64> {
+        match *self {
+            Curve::ConstantProduct | Curve::Weighted { .. } => Ok(curve::initial_liquidity(x, y)),
+            Curve::StableSwap { amp } => to_u64(stable::compute_d(x, y, amp)? / 2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounds_in_the_requested_direction() {
+        for a in 0..64u128 {
+            for b in 0..64u128 {
+                for c in 1..64u128 {
+                    let down = mul_div(a, b, c, false).unwrap();
+                    let up = mul_div(a, b, c, true).unwrap();
+                    assert!(down * c <= a * b && a * b < (down + 1) * c);
+                    assert!(up * c >= a * b && (up == 0 || (up - 1) * c < a * b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mul_div_errors() {
+        assert_eq!(mul_div(1, 1, 0, false), Err(MathError::ZeroBalance));
+        assert_eq!(mul_div(u128::MAX, 2, 1, false), Err(MathError::Overflow));
+        assert_eq!(mul_div(u128::MAX, 1, 1, true), Ok(u128::MAX));
+    }
+
+    #[test]
+    fn mul_div_carries_wide_products() {
+        let big = 1u128 << 100;
+        assert_eq!(mul_div(big, big, big, false), Ok(big));
+        assert_eq!(
+            mul_div(u128::MAX, u128::MAX, u128::MAX, true),
+            Ok(u128::MAX)
+        );
+        assert_eq!(mul_div(u128::MAX, 3, 4, false), Ok(u128::MAX / 4 * 3 + 2));
+        assert_eq!(mul_div(u128::MAX, 3, 4, true), Ok(u128::MAX / 4 * 3 + 3));
+        assert_eq!(mul_div(big, big, 1 << 70, false), Err(MathError::Overflow));
+
+        // agrees with plain u128 arithmetic wherever that fits
+        for a in [1u128, 3, 1 << 40, u64::MAX as u128, 1 << 90] {
+            for b in [1u128 << 37, 1 << 64, (1 << 100) + 7] {
+                for c in [1u128 << 64, (1 << 100) + 1, u128::MAX] {
+                    let quotient = div_wide(mul_wide(a, b), c);
+                    match a.checked_mul(b) {
+                        Some(product) => assert_eq!(quotient, Ok((product / c, product % c))),
+                        None => assert!(quotient.is_ok()),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn to_u64_bounds() {
+        assert_eq!(to_u64(u64::MAX as u128), Ok(u64::MAX));
+        assert_eq!(to_u64(u64::MAX as u128 + 1), Err(MathError::Overflow));
+    }
+}
@@ -0,0 +1,239 @@
+// StableSwap invariant for two tokens, Ann * (x + y) + D = Ann * D + D^3 / (4 * x * y)
+// with Ann = 4 * amp. A high amp keeps the price near 1:1 over a wide band of reserves,
+// amp = 0 would be the constant product curve.
+use crate::curve::{fee_amount, SwapResult, FEE_DENOMINATOR};
+use crate::fairness::scale;
+use crate::{mul_div, to_u64, MathError, Result};
+
+pub const MAX_AMP: u64 = 1_000_000;
+// Newton's method settles in a handful of rounds on any sane pool
+const MAX_ROUNDS: usize = 255;
+
+fn ann(amp: u64) -> Result<u128> {
+    match amp {
+        1..=MAX_AMP => Ok(amp as u128 * 4),
+        _ => Err(MathError::InvalidAmp),
+    }
+}
+
+// D^3 / (4 * x * y), rounded down
+fn d_product(d: u128, x: u128, y: u128) -> Result<u128> {
+    let d_p = mul_div(d, d, x * 2, false)?;
+    mul_div(d_p, d, y * 2, false)
+}
+
+// The invariant D of reserves x and y, by Newton's method from D = x + y
+pub fn compute_d(x: u64, y: u64, amp: u64) -> Result<u128> {
+    let ann = ann(amp)?;
+    if x == 0 || y == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    let (x, y) = (x as u128, y as u128);
+    let sum = x + y;
+    let mut d = sum;
+    for _ in 0..MAX_ROUNDS {
+        let d_p = d_product(d, x, y)?;
+        let previous = d;
+        // D = (Ann * S + 2 * D_P) * D / ((Ann - 1) * D + 3 * D_P)
+        let numerator = ann * sum + d_p.checked_mul(2).ok_or(MathError::Overflow)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)
+            .and_then(|v| v.checked_add(d_p.checked_mul(3)?))
+            .ok_or(MathError::Overflow)?;
+        d = mul_div(numerator, d, denominator, false)?;
+        if d.abs_diff(previous) <= 1 {
+            return Ok(d);
+        }
+    }
+    Err(MathError::NotConverged)
+}
+
+// The other reserve that keeps invariant d once one reserve is x, by Newton's method on
+// y^2 + (x + D / Ann - D) * y = D^3 / (4 * x * Ann). Rounded up.
+pub fn compute_y(x: u64, d: u128, amp: u64) -> Result<u128> {
+    let ann = ann(amp)?;
+    if x == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    let x = x as u128;
+    let c = mul_div(mul_div(d, d, x * 2, false)?, d, ann * 2, false)?;
+    let b = x + d / ann;
+    let mut y = d;
+    for _ in 0..MAX_ROUNDS {
+        let previous = y;
+        // y = (y^2 + c) / (2 * y + b - D)
+        let numerator = y
+            .checked_mul(y)
+            .and_then(|v| v.checked_add(c))
+            .ok_or(MathError::Overflow)?;
+        let denominator = (y * 2 + b).checked_sub(d).ok_or(MathError::Underflow)?;
+        y = mul_div(numerator, 1, denominator, false)?;
+        if y.abs_diff(previous) <= 1 {
+            // the rounding above can land a unit low, the pool keeps the benefit of doubt
+            return Ok(y + 1);
+        }
+    }
+    Err(MathError::NotConverged)
+}
+
+// Output for amount_in. The fee is rounded up and the reserve left behind up, so D
+// never shrinks.
+pub fn swap_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+    amp: u64,
+) -> Result<SwapResult> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+    let fee_amount = fee_amount(amount_in, fee)?;
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let reserve_in_after = to_u64(reserve_in as u128 + (amount_in - fee_amount) as u128)?;
+    let reserve_out_after = compute_y(reserve_in_after, d, amp)?;
+    Ok(SwapResult {
+        deposit: amount_in,
+        withdraw: to_u64((reserve_out as u128).saturating_sub(reserve_out_after))?,
+        fee: fee_amount,
+    })
+}
+
+// An input, rounded up, for which swap_exact_in releases at least amount_out.
+pub fn swap_amount_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+    amp: u64,
+) -> Result<u64> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+    if amount_out >= reserve_out {
+        return Err(MathError::InsufficientBalance);
+    }
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let reserve_in_after = compute_y(reserve_out - amount_out, d, amp)?;
+    // one unit more than the solved reserve covers the rounding of the forward swap
+    let amount_in_after_fee = (reserve_in_after + 1).saturating_sub(reserve_in as u128);
+    let amount_in = mul_div(
+        amount_in_after_fee,
+        FEE_DENOMINATOR as u128,
+        (FEE_DENOMINATOR - fee) as u128,
+        true,
+    )?;
+    to_u64(amount_in)
+}
+
+// Marginal price of the output token in the input token, scaled by 10^precision:
+// in * (Ann * out + D_P) / (out * (Ann * in + D_P)) with D_P = D^3 / (4 * in * out).
+// Rounded down.
+pub fn spot_ratio(reserve_in: u64, reserve_out: u64, amp: u64, precision: u8) -> Result<u128> {
+    let ann = ann(amp)?;
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let (reserve_in, reserve_out) = (reserve_in as u128, reserve_out as u128);
+    let d_p = d_product(d, reserve_in, reserve_out)?;
+    let numerator = (ann * reserve_out)
+        .checked_add(d_p)
+        .ok_or(MathError::Overflow)?;
+    let denominator = (ann * reserve_in)
+        .checked_add(d_p)
+        .ok_or(MathError::Overflow)?;
+    let scaled = mul_div(numerator, scale(precision)?, denominator, false)?;
+    mul_div(scaled, reserve_in, reserve_out, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve;
+    use crate::fairness::limit_price;
+
+    const AMPS: [u64; 4] = [1, 10, 100, 5_000];
+    const RESERVES: [(u64, u64); 5] = [
+        (1_000_000, 1_000_000),
+        (1_000_000, 1_300_000),
+        (5_000_000_000, 4_000_000_000),
+        (1_000_000_000_000, 1_000_000_000_000),
+        (10_000, 900_000),
+    ];
+
+    #[test]
+    fn d_is_the_sum_on_a_balanced_pool() {
+        for amp in AMPS {
+            assert_eq!(compute_d(1_000_000, 1_000_000, amp), Ok(2_000_000));
+        }
+        // an unbalanced pool is worth less than its sum, more so the lower amp
+        let low = compute_d(1_000_000, 1_300_000, 1).unwrap();
+        let high = compute_d(1_000_000, 1_300_000, 1_000).unwrap();
+        assert!(low < high && high < 2_300_000);
+    }
+
+    #[test]
+    fn swap_exact_in_never_shrinks_d() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                for amount_in in [1, 999, 100_000, x / 2] {
+                    for fee in [0, 4, 30] {
+                        let res = swap_exact_in(x, y, amount_in, fee, amp).unwrap();
+                        let before = compute_d(x, y, amp).unwrap();
+                        let after = compute_d(x + amount_in, y - res.withdraw, amp).unwrap();
+                        assert!(after >= before, "amp {amp} {x}/{y} in {amount_in}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stable_beats_constant_product_near_the_peg() {
+        let stable = swap_exact_in(1_000_000, 1_000_000, 100_000, 0, 100).unwrap();
+        let product = curve::swap_exact_in(1_000_000, 1_000_000, 100_000, 0).unwrap();
+        assert!(stable.withdraw > product.withdraw);
+        assert!(stable.withdraw > 99_000 && stable.withdraw < 100_000);
+    }
+
+    #[test]
+    fn swap_amount_in_is_enough() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                for amount_out in [1, 1_000, y / 3] {
+                    for fee in [0, 4, 30] {
+                        let amount_in = swap_amount_in(x, y, amount_out, fee, amp).unwrap();
+                        let res = swap_exact_in(x, y, amount_in, fee, amp).unwrap();
+                        assert!(
+                            res.withdraw >= amount_out,
+                            "amp {amp} {x}/{y} out {amount_out}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spot_ratio_bounds() {
+        // 1:1 on a balanced pool whatever amp
+        for amp in AMPS {
+            assert_eq!(spot_ratio(1_000_000, 1_000_000, amp, 6), Ok(1_000_000));
+        }
+        // between the peg and the constant product price otherwise, closer to the peg
+        // the higher amp is
+        let product = limit_price(1_300_000, 1_000_000, 6).unwrap();
+        let low = spot_ratio(1_300_000, 1_000_000, 1, 6).unwrap();
+        let high = spot_ratio(1_300_000, 1_000_000, 1_000, 6).unwrap();
+        assert!(1_000_000 < high && high < low && low < product);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(compute_d(0, 1, 10), Err(MathError::ZeroBalance));
+        assert_eq!(compute_d(1, 1, 0), Err(MathError::InvalidAmp));
+        assert_eq!(compute_d(1, 1, MAX_AMP + 1), Err(MathError::InvalidAmp));
+        assert_eq!(
+            swap_amount_in(1_000, 1_000, 1_000, 0, 10),
+            Err(MathError::InsufficientBalance)
+        );
+    }
+}
@@ -0,0 +1,384 @@
+// Weighted product curve x^w_x * y^w_y = k for two tokens, as in Balancer pools. Weights
+// are whole percents summing to 100, so an exponent w_in / w_out is a small fraction
+// p / q and every power reduces to an integer power and an integer root. Only splits
+// whose roots stay within a swap's compute budget are accepted.
+use crate::curve::{fee_amount, SwapResult, FEE_DENOMINATOR};
+use crate::fairness::weighted_limit_price;
+use crate::{mul_div, to_u64, MathError, Result};
+
+pub const WEIGHT_TOTAL: u8 = 100;
+// the root is a binary search over q-th powers, so the weight of either side is one of a
+// few splits with small reduced ratios (at most 4 / 1), each checked for compute units in
+// the weighted_pool integration tests
+pub const SUPPORTED_WEIGHTS: [u8; 5] = [20, 40, 50, 60, 80];
+// a swap can't take in or pay out more than 30% of a reserve, which also keeps the
+// fixed-point powers below well inside u128
+pub const MAX_RATIO_BPS: u16 = 3_000;
+// fixed-point one for the powers
+const ONE: u128 = 1_000_000_000_000_000_000_000;
+// a few rounds of backing off cover the rounding of the fixed-point powers
+const MAX_ROUNDS: usize = 64;
+
+pub fn is_valid_weights(weight_x: u8, weight_y: u8) -> bool {
+    SUPPORTED_WEIGHTS.contains(&weight_x)
+        && weight_x as u16 + weight_y as u16 == WEIGHT_TOTAL as u16
+}
+
+fn check_weights(weight_in: u8, weight_out: u8) -> Result<()> {
+    match is_valid_weights(weight_in, weight_out) {
+        true => Ok(()),
+        false => Err(MathError::InvalidWeights),
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+// amount * MAX_RATIO_BPS / FEE_DENOMINATOR, rounded down
+fn max_trade(reserve: u64) -> Result<u128> {
+    mul_div(
+        reserve as u128,
+        MAX_RATIO_BPS as u128,
+        FEE_DENOMINATOR as u128,
+        false,
+    )
+}
+
+// base^exponent in fixed point, rounded down or up
+fn pow(base: u128, exponent: u32, round_up: bool) -> Result<u128> {
+    let mut result = ONE;
+    let mut square = base;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_div(result, square, ONE, round_up)?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            square = mul_div(square, square, ONE, round_up)?;
+        }
+    }
+    Ok(result)
+}
+
+// The n-th root of value in fixed point, rounded up: the smallest z whose n-th power,
+// rounded down, reaches value
+fn root_up(value: u128, n: u32) -> Result<u128> {
+    if n == 1 {
+        return Ok(value);
+    }
+    // the root lies between value and one
+    let (mut low, mut high) = (value.min(ONE), value.max(ONE));
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let reaches = match pow(mid, n, false) {
+            Ok(power) => power >= value,
+            Err(MathError::Overflow) => true,
+            Err(e) => return Err(e),
+        };
+        match reaches {
+            true => high = mid,
+            false => low = mid + 1,
+        }
+    }
+    Ok(low)
+}
+
+// base^(numerator / denominator) in fixed point, rounded up
+fn pow_fraction(base: u128, numerator: u8, denominator: u8) -> Result<u128> {
+    let divisor = gcd(numerator as u32, denominator as u32);
+    let power = pow(base, numerator as u32 / divisor, true)?;
+    root_up(power, denominator as u32 / divisor)
+}
+
+// Output for amount_in, reserve_out * (1 - (in / (in + amount_in))^(w_in / w_out)). The
+// fee and the share of reserve_out left behind are rounded up, so k never shrinks.
+pub fn swap_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+    weight_in: u8,
+    weight_out: u8,
+) -> Result<SwapResult> {
+    check_weights(weight_in, weight_out)?;
+    if fee >= FEE_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    let fee_amount = fee_amount(amount_in, fee)?;
+    let amount_in_after_fee = (amount_in - fee_amount) as u128;
+    if amount_in_after_fee > max_trade(reserve_in)? {
+        return Err(MathError::TradeTooLarge);
+    }
+    let base = mul_div(
+        reserve_in as u128,
+        ONE,
+        reserve_in as u128 + amount_in_after_fee,
+        true,
+    )?;
+    let remaining = pow_fraction(base, weight_in, weight_out)?.min(ONE);
+    Ok(SwapResult {
+        deposit: amount_in,
+        withdraw: to_u64(mul_div(reserve_out as u128, ONE - remaining, ONE, false)?)?,
+        fee: fee_amount,
+    })
+}
+
+// An input for which swap_exact_in releases at least amount_out. Solved from
+// in * ((out / (out - amount_out))^(w_out / w_in) - 1), then raised until the forward
+// swap, with its own rounding, pays out enough.
+pub fn swap_amount_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+    weight_in: u8,
+    weight_out: u8,
+) -> Result<u64> {
+    check_weights(weight_in, weight_out)?;
+    if fee >= FEE_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(MathError::ZeroBalance);
+    }
+    if amount_out as u128 > max_trade(reserve_out)? {
+        return Err(MathError::TradeTooLarge);
+    }
+    let base = mul_div(
+        reserve_out as u128,
+        ONE,
+        (reserve_out - amount_out) as u128,
+        true,
+    )?;
+    let growth = pow_fraction(base, weight_out, weight_in)?;
+    let amount_in_after_fee = mul_div(reserve_in as u128, growth - ONE, ONE, true)?;
+    let mut amount_in = to_u64(mul_div(
+        amount_in_after_fee,
+        FEE_DENOMINATOR as u128,
+        (FEE_DENOMINATOR - fee) as u128,
+        true,
+    )?)?;
+
+    let mut step = (amount_in >> 40).max(1);
+    for _ in 0..MAX_ROUNDS {
+        let res = swap_exact_in(
+            reserve_in,
+            reserve_out,
+            amount_in,
+            fee,
+            weight_in,
+            weight_out,
+        )?;
+        if res.withdraw >= amount_out {
+            return Ok(amount_in);
+        }
+        amount_in = amount_in.checked_add(step).ok_or(MathError::Overflow)?;
+        step = step.saturating_mul(2);
+    }
+    Err(MathError::NotConverged)
+}
+
+// Marginal price of the output token in the input token, scaled by 10^precision:
+// (in / w_in) / (out / w_out). Rounded down.
+pub fn spot_ratio(
+    reserve_in: u64,
+    reserve_out: u64,
+    weight_in: u8,
+    weight_out: u8,
+    precision: u8,
+) -> Result<u128> {
+    check_weights(weight_in, weight_out)?;
+    weighted_limit_price(reserve_in, weight_in, reserve_out, weight_out, precision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve;
+
+    const WEIGHTS: [(u8, u8); 5] = [(50, 50), (80, 20), (20, 80), (60, 40), (40, 60)];
+    const RESERVES: [(u64, u64); 4] = [
+        (1_000_000, 1_000_000),
+        (4_000_000, 1_000_000),
+        (5_000_000_000, 70_000_000),
+        (1_000_000_000_000_000, 3_000_000_000_000),
+    ];
+
+    // x^w_x * y^w_y compared through logarithms, enough to catch an invariant that shrinks
+    fn log_invariant(x: u64, y: u64, weight_x: u8, weight_y: u8) -> f64 {
+        weight_x as f64 * ln(x as f64) + weight_y as f64 * ln(y as f64)
+    }
+
+    // core has no f64::ln, a series is plenty for a test
+    fn ln(value: f64) -> f64 {
+        let mut exponent = 0.0;
+        let mut mantissa = value;
+        while mantissa > 2.0 {
+            mantissa /= 2.0;
+            exponent += 1.0;
+        }
+        let t = (mantissa - 1.0) / (mantissa + 1.0);
+        let mut sum = 0.0;
+        let mut term = t;
+        let mut k = 1.0;
+        while k < 60.0 {
+            sum += term / k;
+            term *= t * t;
+            k += 2.0;
+        }
+        2.0 * sum + exponent * core::f64::consts::LN_2
+    }
+
+    #[test]
+    fn equal_weights_match_constant_product() {
+        for (reserve_in, reserve_out) in RESERVES {
+            for amount_in in [1, 1_000, reserve_in / 10, reserve_in / 4] {
+                let weighted = swap_exact_in(reserve_in, reserve_out, amount_in, 30, 50, 50);
+                let product = curve::swap_exact_in(reserve_in, reserve_out, amount_in, 30);
+                let (weighted, product) = (weighted.unwrap(), product.unwrap());
+                assert_eq!(weighted.fee, product.fee);
+                // the fixed-point power rounds up, so the output may trail by a unit
+                assert!(weighted.withdraw <= product.withdraw);
+                assert!(product.withdraw - weighted.withdraw <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn swap_exact_in_never_shrinks_k() {
+        for (weight_in, weight_out) in WEIGHTS {
+            for (reserve_in, reserve_out) in RESERVES {
+                for amount_in in [1, 977, reserve_in / 100, reserve_in / 4] {
+                    let res =
+                        swap_exact_in(reserve_in, reserve_out, amount_in, 0, weight_in, weight_out)
+                            .unwrap();
+                    let before = log_invariant(reserve_in, reserve_out, weight_in, weight_out);
+                    let after = log_invariant(
+                        reserve_in + amount_in,
+                        reserve_out - res.withdraw,
+                        weight_in,
+                        weight_out,
+                    );
+                    assert!(after >= before - 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_trades_fill_near_spot() {
+        // 4 X against 1 Y at 80/20 is a 1:1 price, either way round
+        let x_for_y = swap_exact_in(4_000_000, 1_000_000, 1_000, 0, 80, 20).unwrap();
+        let y_for_x = swap_exact_in(1_000_000, 4_000_000, 1_000, 0, 20, 80).unwrap();
+        assert!((999..=1_000).contains(&x_for_y.withdraw));
+        assert!((999..=1_000).contains(&y_for_x.withdraw));
+    }
+
+    #[test]
+    fn swap_amount_in_is_enough() {
+        for (weight_in, weight_out) in WEIGHTS {
+            for (reserve_in, reserve_out) in RESERVES {
+                for amount_out in [1, 1_000, reserve_out / 100, reserve_out / 4] {
+                    let amount_in = match swap_amount_in(
+                        reserve_in,
+                        reserve_out,
+                        amount_out,
+                        30,
+                        weight_in,
+                        weight_out,
+                    ) {
+                        // past 30% of the input reserve
+                        Err(MathError::TradeTooLarge) => continue,
+                        res => res.unwrap(),
+                    };
+                    let res = swap_exact_in(
+                        reserve_in,
+                        reserve_out,
+                        amount_in,
+                        30,
+                        weight_in,
+                        weight_out,
+                    )
+                    .unwrap();
+                    assert!(res.withdraw >= amount_out);
+                    // and not by much
+                    let less = amount_in.saturating_sub(amount_in / 1_000_000 + 2);
+                    if let Ok(res) =
+                        swap_exact_in(reserve_in, reserve_out, less, 30, weight_in, weight_out)
+                    {
+                        assert!(res.withdraw <= amount_out);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spot_ratio_weights_the_reserves() {
+        // 4 X against 1 Y at 80/20 prices X and Y equally
+        assert_eq!(spot_ratio(4_000_000, 1_000_000, 80, 20, 6), Ok(1_000_000));
+        assert_eq!(spot_ratio(1_000_000, 4_000_000, 20, 80, 6), Ok(1_000_000));
+        assert_eq!(
+            spot_ratio(3_000_000, 2_000_000, 50, 50, 6),
+            crate::fairness::limit_price(3_000_000, 2_000_000, 6)
+        );
+    }
+
+    #[test]
+    fn powers_round_in_the_requested_direction() {
+        let half = ONE / 2;
+        assert_eq!(pow(half, 2, false), Ok(ONE / 4));
+        assert_eq!(pow(ONE, 99, true), Ok(ONE));
+        assert_eq!(root_up(ONE / 4, 2), Ok(half));
+        assert_eq!(root_up(ONE * 8, 3), Ok(ONE * 2));
+        // the root of a third, cubed back, lands at or above it
+        let root = root_up(ONE / 3, 3).unwrap();
+        assert!(pow(root, 3, false).unwrap() >= ONE / 3);
+        assert!(pow(root - 1, 3, false).unwrap() < ONE / 3);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            swap_exact_in(1_000, 1_000, 10, 30, 80, 30),
+            Err(MathError::InvalidWeights)
+        );
+        assert_eq!(
+            swap_exact_in(1_000, 1_000, 10, 30, 100, 0),
+            Err(MathError::InvalidWeights)
+        );
+        // adds up, but a 37th root is too costly on-chain
+        assert_eq!(
+            swap_exact_in(1_000, 1_000, 10, 30, 63, 37),
+            Err(MathError::InvalidWeights)
+        );
+        assert_eq!(
+            swap_exact_in(1_000, 0, 10, 30, 80, 20),
+            Err(MathError::ZeroBalance)
+        );
+        assert_eq!(
+            swap_exact_in(1_000, 1_000, 10, 10_000, 80, 20),
+            Err(MathError::InvalidFee)
+        );
+        assert_eq!(
+            swap_exact_in(1_000, 1_000, 302, 0, 80, 20),
+            Err(MathError::TradeTooLarge)
+        );
+        assert_eq!(
+            swap_amount_in(1_000, 1_000, 301, 30, 80, 20),
+            Err(MathError::TradeTooLarge)
+        );
+        assert_eq!(
+            spot_ratio(1_000, 1_000, 0, 100, 6),
+            Err(MathError::InvalidWeights)
+        );
+    }
+}
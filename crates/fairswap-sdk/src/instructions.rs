@@ -0,0 +1,660 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use anchor_spl::token;
+
+use fairswap::states::{CurveType, Deadline, SurplusPolicy, SwapCommitment};
+
+use crate::pda::{
+    find_auth, find_batch, find_batch_order, find_commitment, find_config, find_limit_order,
+    find_locked_lp, find_mint_lp, find_pooldata, find_vault,
+};
+
+// Every address of one pool, with the token program owning each mint
+#[derive(Clone, Debug)]
+pub struct PoolKeys {
+    pub seed: u64,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub token_program_x: Pubkey,
+    pub token_program_y: Pubkey,
+    // chosen by the pool's first deposit
+    pub token_program_lp: Pubkey,
+    pub config: Pubkey,
+    pub pooldata: Pubkey,
+    pub auth: Pubkey,
+    pub mint_lp: Pubkey,
+    pub locked_lp: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+}
+
+impl PoolKeys {
+    // Pool of two legacy SPL Token mints
+    pub fn new(mint_x: Pubkey, mint_y: Pubkey, seed: u64) -> Self {
+        Self::with_token_programs(mint_x, mint_y, seed, token::ID, token::ID, token::ID)
+    }
+
+    pub fn with_token_programs(
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        seed: u64,
+        token_program_x: Pubkey,
+        token_program_y: Pubkey,
+        token_program_lp: Pubkey,
+    ) -> Self {
+        let config = find_config(&mint_x, &mint_y, seed).0;
+        Self {
+            seed,
+            mint_x,
+            mint_y,
+            token_program_x,
+            token_program_y,
+            token_program_lp,
+            config,
+            pooldata: find_pooldata(&config).0,
+            auth: find_auth(&config).0,
+            mint_lp: find_mint_lp(&config).0,
+            locked_lp: find_locked_lp(&config).0,
+            vault_x: find_vault(&config, &mint_x).0,
+            vault_y: find_vault(&config, &mint_y).0,
+        }
+    }
+
+    pub fn ata_x(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.mint_x, &self.token_program_x)
+    }
+
+    pub fn ata_y(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.mint_y, &self.token_program_y)
+    }
+
+    pub fn ata_lp(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.mint_lp, &self.token_program_lp)
+    }
+
+    pub fn initialize(
+        &self,
+        admin: &Pubkey,
+        fee: u16,
+        protocol_fee_bps: u16,
+        window_slots: u64,
+        treasury: Pubkey,
+        surplus_policy: SurplusPolicy,
+    ) -> Instruction {
+        self.initialize_with_curve(
+            admin,
+            fee,
+            protocol_fee_bps,
+            window_slots,
+            treasury,
+            surplus_policy,
+            CurveType::ConstantProduct,
+            0,
+            50,
+            50,
+        )
+    }
+
+    // amp must be 0 unless the curve is StableSwap, and the weights 50 / 50 unless it is
+    // weighted
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_with_curve(
+        &self,
+        admin: &Pubkey,
+        fee: u16,
+        protocol_fee_bps: u16,
+        window_slots: u64,
+        treasury: Pubkey,
+        surplus_policy: SurplusPolicy,
+        curve: CurveType,
+        amp: u64,
+        weight_x: u8,
+        weight_y: u8,
+    ) -> Instruction {
+        let accounts = fairswap::accounts::Initialize {
+            admin: *admin,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            auth: self.auth,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            system_program: system_program::ID,
+        };
+        let data = fairswap::instruction::Initialize {
+            seed: self.seed,
+            fee,
+            protocol_fee_bps,
+            window_slots,
+            treasury,
+            surplus_policy,
+            curve,
+            amp,
+            weight_x,
+            weight_y,
+        };
+        instruction(accounts, data)
+    }
+
+    pub fn deposit(
+        &self,
+        user: &Pubkey,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        deadline: Deadline,
+    ) -> Instruction {
+        let accounts = fairswap::accounts::Deposit {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            mint_lp: self.mint_lp,
+            user_ata_lp: self.ata_lp(user),
+            locked_lp: self.locked_lp,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            token_program_lp: self.token_program_lp,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        let data = fairswap::instruction::Deposit {
+            amount,
+            max_x,
+            max_y,
+            deadline,
+        };
+        instruction(accounts, data)
+    }
+
+    pub fn withdraw(
+        &self,
+        user: &Pubkey,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        deadline: Deadline,
+    ) -> Instruction {
+        let accounts = fairswap::accounts::Withdraw {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            user_ata_lp: self.ata_lp(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            mint_lp: self.mint_lp,
+            config: self.config,
+            pooldata: self.pooldata,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            token_program_lp: self.token_program_lp,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        let data = fairswap::instruction::Withdraw {
+            amount,
+            min_x,
+            min_y,
+            deadline,
+        };
+        instruction(accounts, data)
+    }
+
+    pub fn swap(
+        &self,
+        user: &Pubkey,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        deadline: Deadline,
+    ) -> Instruction {
+        let data = fairswap::instruction::Swap {
+            mint_deposit,
+            amount_in,
+            amount_out_min,
+            deadline,
+        };
+        instruction(self.swap_accounts(user), data)
+    }
+
+    pub fn swap_exact_out(
+        &self,
+        user: &Pubkey,
+        mint_deposit: Pubkey,
+        amount_out: u64,
+        max_amount_in: u64,
+        deadline: Deadline,
+    ) -> Instruction {
+        let data = fairswap::instruction::SwapExactOut {
+            mint_deposit,
+            amount_out,
+            max_amount_in,
+            deadline,
+        };
+        instruction(self.swap_accounts(user), data)
+    }
+
+    fn swap_accounts(&self, user: &Pubkey) -> fairswap::accounts::Swap {
+        fairswap::accounts::Swap {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            mint_lp: self.mint_lp,
+            config: self.config,
+            pooldata: self.pooldata,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    // Read-only, simulate it to get the SwapQuote from the return data
+    pub fn quote(&self, mint_deposit: Pubkey, amount_in: u64, amount_out_min: u64) -> Instruction {
+        let accounts = fairswap::accounts::Quote {
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            mint_lp: self.mint_lp,
+            config: self.config,
+            pooldata: self.pooldata,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+        };
+        let data = fairswap::instruction::Quote {
+            mint_deposit,
+            amount_in,
+            amount_out_min,
+        };
+        instruction(accounts, data)
+    }
+
+    // Escrows amount_in in the batch clearing after `slot`, refunded on claim if it
+    // would pay out less than amount_out_min
+    pub fn place_batch_order(
+        &self,
+        user: &Pubkey,
+        slot: u64,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+    ) -> Instruction {
+        let batch = find_batch(&self.config, slot).0;
+        let accounts = fairswap::accounts::PlaceBatchOrder {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            batch,
+            order: find_batch_order(&batch, user).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        let data = fairswap::instruction::PlaceBatchOrder {
+            slot,
+            mint_deposit,
+            amount_in,
+            amount_out_min,
+        };
+        instruction(accounts, data)
+    }
+
+    // Permissionless, only the fee payer signs
+    pub fn clear_batch(&self, slot: u64) -> Instruction {
+        let accounts = fairswap::accounts::ClearBatch {
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            batch: find_batch(&self.config, slot).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+        };
+        instruction(accounts, fairswap::instruction::ClearBatch {})
+    }
+
+    // batch_payer is Batch::payer, the account that created the batch
+    pub fn claim_batch_order(&self, user: &Pubkey, slot: u64, batch_payer: &Pubkey) -> Instruction {
+        let batch = find_batch(&self.config, slot).0;
+        let accounts = fairswap::accounts::ClaimBatchOrder {
+            user: *user,
+            batch_payer: *batch_payer,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            batch,
+            order: find_batch_order(&batch, user).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, fairswap::instruction::ClaimBatchOrder {})
+    }
+
+    // recipient is the owner the pool's surplus policy pays out to
+    // Escrows amount_x and amount_y behind `hash`, see SwapCommitment::hash
+    pub fn commit_swap(
+        &self,
+        user: &Pubkey,
+        hash: [u8; 32],
+        amount_x: u64,
+        amount_y: u64,
+    ) -> Instruction {
+        let accounts = fairswap::accounts::CommitSwap {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            commitment: find_commitment(&self.config, user, &hash).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        let data = fairswap::instruction::CommitSwap {
+            hash,
+            amount_x,
+            amount_y,
+        };
+        instruction(accounts, data)
+    }
+
+    pub fn reveal_swap(
+        &self,
+        user: &Pubkey,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        salt: [u8; 32],
+    ) -> Instruction {
+        let hash = SwapCommitment::hash(&mint_deposit, amount_in, amount_out_min, &salt);
+        let accounts = fairswap::accounts::RevealSwap {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            commitment: find_commitment(&self.config, user, &hash).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        let data = fairswap::instruction::RevealSwap {
+            mint_deposit,
+            amount_in,
+            amount_out_min,
+            salt,
+        };
+        instruction(accounts, data)
+    }
+
+    pub fn refund_commitment(&self, user: &Pubkey, hash: [u8; 32]) -> Instruction {
+        let accounts = fairswap::accounts::RefundCommitment {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            commitment: find_commitment(&self.config, user, &hash).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, fairswap::instruction::RefundCommitment {})
+    }
+
+    // min_ratio is output per unit of input, scaled by 10^precision like every pool ratio
+    pub fn place_limit_order(
+        &self,
+        user: &Pubkey,
+        id: u64,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        min_ratio: u128,
+        keeper_reward: u64,
+    ) -> Instruction {
+        let accounts = fairswap::accounts::PlaceLimitOrder {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            order: find_limit_order(&self.config, user, id).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        let data = fairswap::instruction::PlaceLimitOrder {
+            id,
+            mint_deposit,
+            amount_in,
+            min_ratio,
+            keeper_reward,
+        };
+        instruction(accounts, data)
+    }
+
+    pub fn fill_limit_order(&self, keeper: &Pubkey, owner: &Pubkey, id: u64) -> Instruction {
+        let accounts = fairswap::accounts::FillLimitOrder {
+            keeper: *keeper,
+            owner: *owner,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            owner_ata_x: self.ata_x(owner),
+            owner_ata_y: self.ata_y(owner),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            order: find_limit_order(&self.config, owner, id).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+        };
+        instruction(accounts, fairswap::instruction::FillLimitOrder {})
+    }
+
+    pub fn cancel_limit_order(&self, user: &Pubkey, id: u64) -> Instruction {
+        let accounts = fairswap::accounts::CancelLimitOrder {
+            user: *user,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            user_ata_x: self.ata_x(user),
+            user_ata_y: self.ata_y(user),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            order: find_limit_order(&self.config, user, id).0,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, fairswap::instruction::CancelLimitOrder {})
+    }
+
+    pub fn collect_surplus(&self, payer: &Pubkey, recipient: &Pubkey) -> Instruction {
+        let accounts = fairswap::accounts::CollectSurplus {
+            payer: *payer,
+            recipient: *recipient,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            recipient_ata_x: self.ata_x(recipient),
+            recipient_ata_y: self.ata_y(recipient),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, fairswap::instruction::CollectSurplus {})
+    }
+
+    pub fn collect_protocol_fees(&self, authority: &Pubkey, treasury: &Pubkey) -> Instruction {
+        let accounts = fairswap::accounts::CollectProtocolFees {
+            authority: *authority,
+            treasury: *treasury,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            auth: self.auth,
+            treasury_ata_x: self.ata_x(treasury),
+            treasury_ata_y: self.ata_y(treasury),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, fairswap::instruction::CollectProtocolFees {})
+    }
+
+    pub fn lock(&self, admin: &Pubkey) -> Instruction {
+        self.update(admin, fairswap::instruction::Lock {})
+    }
+
+    pub fn unlock(&self, admin: &Pubkey) -> Instruction {
+        self.update(admin, fairswap::instruction::Unlock {})
+    }
+
+    pub fn set_window_slots(&self, admin: &Pubkey, window_slots: u64) -> Instruction {
+        self.update(
+            admin,
+            fairswap::instruction::SetWindowSlots { window_slots },
+        )
+    }
+
+    pub fn set_surplus_policy(&self, admin: &Pubkey, surplus_policy: SurplusPolicy) -> Instruction {
+        self.update(
+            admin,
+            fairswap::instruction::SetSurplusPolicy { surplus_policy },
+        )
+    }
+
+    pub fn set_batch_auction(&self, admin: &Pubkey, batch_auction: bool) -> Instruction {
+        self.update(
+            admin,
+            fairswap::instruction::SetBatchAuction { batch_auction },
+        )
+    }
+
+    pub fn ramp_amp(&self, admin: &Pubkey, target_amp: u64, end_ts: i64) -> Instruction {
+        self.update(admin, fairswap::instruction::RampAmp { target_amp, end_ts })
+    }
+
+    pub fn stop_ramp_amp(&self, admin: &Pubkey) -> Instruction {
+        self.update(admin, fairswap::instruction::StopRampAmp {})
+    }
+
+    pub fn set_fee(&self, admin: &Pubkey, fee: u16, protocol_fee_bps: u16) -> Instruction {
+        self.update(
+            admin,
+            fairswap::instruction::SetFee {
+                fee,
+                protocol_fee_bps,
+            },
+        )
+    }
+
+    pub fn propose_authority(&self, admin: &Pubkey, new_authority: Pubkey) -> Instruction {
+        self.update(
+            admin,
+            fairswap::instruction::ProposeAuthority { new_authority },
+        )
+    }
+
+    // signed by the proposed authority
+    pub fn accept_authority(&self, new_authority: &Pubkey) -> Instruction {
+        self.update(new_authority, fairswap::instruction::AcceptAuthority {})
+    }
+
+    pub fn renounce_authority(&self, admin: &Pubkey) -> Instruction {
+        self.update(admin, fairswap::instruction::RenounceAuthority {})
+    }
+
+    fn update(&self, admin: &Pubkey, data: impl InstructionData) -> Instruction {
+        let accounts = fairswap::accounts::Update {
+            admin: *admin,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            config: self.config,
+            pooldata: self.pooldata,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, data)
+    }
+}
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: fairswap::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
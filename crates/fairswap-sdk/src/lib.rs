@@ -0,0 +1,13 @@
+// Off-chain counterpart of the fairswap program. Account layouts, instruction data and
+// the fairness pricing all come from the program crate itself, so bots and backends
+// stay in step with whatever is deployed from this workspace.
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+pub mod state;
+
+pub use fairswap::states::{Config, CurveType, Deadline, PoolData, SurplusPolicy};
+pub use fairswap::{SwapQuote, ID};
+pub use fairswap_math as math;
+pub use instructions::PoolKeys;
+pub use quote::PoolState;
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::Pubkey;
+
+pub fn find_config(mint_x: &Pubkey, mint_y: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            &seed.to_le_bytes(),
+        ],
+        &fairswap::ID,
+    )
+}
+
+pub fn find_pooldata(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pooldata", config.as_ref()], &fairswap::ID)
+}
+
+// signs for the vaults and mints LP
+pub fn find_auth(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"auth", config.as_ref()], &fairswap::ID)
+}
+
+pub fn find_mint_lp(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mint_lp", config.as_ref()], &fairswap::ID)
+}
+
+// holds the MINIMUM_LIQUIDITY minted on the first deposit
+pub fn find_locked_lp(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"locked_lp", config.as_ref()], &fairswap::ID)
+}
+
+pub fn find_vault(config: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", config.as_ref(), mint.as_ref()], &fairswap::ID)
+}
+
+// batch of a batch-auction pool clearing after `slot`
+pub fn find_batch(config: &Pubkey, slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"batch", config.as_ref(), &slot.to_le_bytes()],
+        &fairswap::ID,
+    )
+}
+
+pub fn find_batch_order(batch: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"order", batch.as_ref(), owner.as_ref()], &fairswap::ID)
+}
+
+// swap commitment of `owner`, keyed by the hash it commits to
+pub fn find_commitment(config: &Pubkey, owner: &Pubkey, hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"commitment", config.as_ref(), owner.as_ref(), hash],
+        &fairswap::ID,
+    )
+}
+
+pub fn find_limit_order(config: &Pubkey, owner: &Pubkey, id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"limit_order",
+            config.as_ref(),
+            owner.as_ref(),
+            &id.to_le_bytes(),
+        ],
+        &fairswap::ID,
+    )
+}
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+
+use fairswap::errors::AmmError;
+use fairswap::states::{Config, PoolData};
+use fairswap::{assert_non_zero, assert_not_locked, SwapQuote};
+
+use crate::state::{
+    decode_config, decode_mint_supply, decode_pooldata, decode_token_amount,
+    decode_transfer_fee_config,
+};
+
+// Everything Swap::swap reads to price a swap
+#[derive(Clone)]
+pub struct PoolState {
+    pub config: Config,
+    pub pooldata: PoolData,
+    pub vault_x: u64,
+    pub vault_y: u64,
+    pub lp_supply: u64,
+    pub transfer_fee_x: Option<TransferFeeConfig>,
+    pub transfer_fee_y: Option<TransferFeeConfig>,
+}
+
+impl PoolState {
+    // Builds the state from raw account data, mints are passed as (owner, data)
+    pub fn decode(
+        config: &[u8],
+        pooldata: &[u8],
+        vault_x: &[u8],
+        vault_y: &[u8],
+        mint_lp: &[u8],
+        mint_x: (&Pubkey, &[u8]),
+        mint_y: (&Pubkey, &[u8]),
+    ) -> Result<Self> {
+        Ok(Self {
+            config: decode_config(config)?,
+            pooldata: decode_pooldata(pooldata)?,
+            vault_x: decode_token_amount(vault_x)?,
+            vault_y: decode_token_amount(vault_y)?,
+            lp_supply: decode_mint_supply(mint_lp)?,
+            transfer_fee_x: decode_transfer_fee_config(mint_x.0, mint_x.1),
+            transfer_fee_y: decode_transfer_fee_config(mint_y.0, mint_y.1),
+        })
+    }
+
+    // Outcome of Swap::swap at the given clock, without changing the state
+    pub fn quote_swap(
+        &self,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        clock: &Clock,
+    ) -> Result<SwapQuote> {
+        self.clone()
+            .swap(mint_deposit, amount_in, amount_out_min, clock)
+    }
+
+    // Runs Swap::swap step for step and leaves the state as the program would, so a
+    // sequence of swaps in one window can be simulated by calling this repeatedly
+    pub fn swap(
+        &mut self,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        clock: &Clock,
+    ) -> Result<SwapQuote> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+        assert_non_zero!([amount_in, amount_out_min]);
+
+        let is_buying_x = match mint_deposit {
+            m if m == self.config.mint_x => false,
+            m if m == self.config.mint_y => true,
+            _ => return Err(AmmError::InvalidInputMint.into()),
+        };
+        let (fee_in, fee_out) = match is_buying_x {
+            true => (self.transfer_fee_y, self.transfer_fee_x),
+            false => (self.transfer_fee_x, self.transfer_fee_y),
+        };
+
+        let vault_amount_in = amount_in - transfer_fee(fee_in.as_ref(), clock, amount_in)?;
+        let reserves = self.pooldata.reserves(self.vault_x, self.vault_y)?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        self.pooldata
+            .update_oracle(reserves.0, reserves.1, curve, clock.unix_timestamp)?;
+        let res = self.pooldata.fair_swap(
+            reserves,
+            curve,
+            self.config.fee,
+            is_buying_x,
+            vault_amount_in,
+            0,
+            clock.slot,
+        )?;
+        self.pooldata
+            .accrue_protocol_fee(!is_buying_x, res.fee, self.config.protocol_fee_bps)?;
+        self.pooldata.capture_surplus(
+            is_buying_x,
+            res.raw_withdraw - res.withdraw,
+            self.config.surplus_policy,
+        )?;
+
+        let amount_received = res.withdraw - transfer_fee(fee_out.as_ref(), clock, res.withdraw)?;
+        require!(
+            amount_received >= amount_out_min,
+            AmmError::SlippageExceeded
+        );
+        assert_non_zero!([res.deposit, amount_received]);
+
+        // the vault is credited with what arrives after the input transfer fee
+        let (vault_in, vault_out) = match is_buying_x {
+            true => (&mut self.vault_y, &mut self.vault_x),
+            false => (&mut self.vault_x, &mut self.vault_y),
+        };
+        *vault_in = vault_in
+            .checked_add(vault_amount_in)
+            .ok_or(AmmError::Overflow)?;
+        *vault_out = vault_out
+            .checked_sub(res.withdraw)
+            .ok_or(AmmError::Underflow)?;
+
+        Ok(SwapQuote {
+            amount_in,
+            amount_out_raw: res.raw_withdraw,
+            amount_out: amount_received,
+            ratio: res.ratio,
+            window_start_slot: self.pooldata.last_slot,
+            window_slots: self.pooldata.window_slots,
+        })
+    }
+}
+
+fn transfer_fee(config: Option<&TransferFeeConfig>, clock: &Clock, amount: u64) -> Result<u64> {
+    match config {
+        Some(config) => Ok(config
+            .calculate_epoch_fee(clock.epoch, amount)
+            .ok_or(AmmError::Overflow)?),
+        None => Ok(0),
+    }
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::{Account as TokenAccount, Mint},
+};
+
+use fairswap::states::{Batch, BatchOrder, Config, LimitOrder, PoolData, SwapCommitment};
+
+// Both decoders check the account discriminator, so a wrong address fails instead of
+// decoding garbage
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    Config::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_pooldata(data: &[u8]) -> Result<PoolData> {
+    PoolData::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_batch(data: &[u8]) -> Result<Batch> {
+    Batch::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_batch_order(data: &[u8]) -> Result<BatchOrder> {
+    BatchOrder::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_commitment(data: &[u8]) -> Result<SwapCommitment> {
+    SwapCommitment::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_limit_order(data: &[u8]) -> Result<LimitOrder> {
+    LimitOrder::try_deserialize(&mut &data[..])
+}
+
+// Balance of a legacy or Token-2022 token account, e.g. a pool vault
+pub fn decode_token_amount(data: &[u8]) -> Result<u64> {
+    StateWithExtensions::<TokenAccount>::unpack(data)
+        .map(|account| account.base.amount)
+        .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+pub fn decode_mint_supply(data: &[u8]) -> Result<u64> {
+    StateWithExtensions::<Mint>::unpack(data)
+        .map(|mint| mint.base.supply)
+        .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+// Transfer fee config of a Token-2022 mint, None for legacy mints and mints without one
+pub fn decode_transfer_fee_config(owner: &Pubkey, data: &[u8]) -> Option<TransferFeeConfig> {
+    if *owner != anchor_spl::token_2022::ID {
+        return None;
+    }
+    StateWithExtensions::<Mint>::unpack(data)
+        .ok()?
+        .get_extension::<TransferFeeConfig>()
+        .ok()
+        .copied()
+}
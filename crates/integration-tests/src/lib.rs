@@ -0,0 +1,583 @@
+// Shared setup for the in-process tests, the programs are loaded from target/deploy
+// so run `anchor build` before `cargo test`.
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    get_associated_token_address,
+    spl_associated_token_account::instruction::create_associated_token_account,
+};
+use anchor_spl::token::spl_token;
+use litesvm::{types::TransactionResult, LiteSVM};
+use solana_sdk::{
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+use fairswap::states::{Config, CurveType, Deadline, PoolData, SurplusPolicy};
+use fairswap_sdk::math::Curve;
+use fairswap_sdk::{PoolKeys, PoolState};
+
+pub const LAMPORTS: u64 = 100_000_000_000;
+
+pub fn setup() -> LiteSVM {
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(fairswap::ID, program_path("fairswap"))
+        .expect("fairswap.so missing, run `anchor build`");
+    svm.add_program_from_file(simple_amm::ID, program_path("simple_amm"))
+        .expect("simple_amm.so missing, run `anchor build`");
+    svm
+}
+
+fn program_path(name: &str) -> String {
+    format!(
+        "{}/../../target/deploy/{}.so",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    )
+}
+
+pub fn new_user(svm: &mut LiteSVM) -> Keypair {
+    let user = Keypair::new();
+    svm.airdrop(&user.pubkey(), LAMPORTS).unwrap();
+    user
+}
+
+// Signs with the payer plus any extra signers, a fresh blockhash keeps repeated
+// identical instructions from being rejected as already processed
+#[allow(clippy::result_large_err)]
+pub fn send(
+    svm: &mut LiteSVM,
+    ixs: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> TransactionResult {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&payer.pubkey()),
+        &all_signers,
+        svm.latest_blockhash(),
+    );
+    let res = svm.send_transaction(tx);
+    svm.expire_blockhash();
+    res
+}
+
+pub fn current_slot(svm: &LiteSVM) -> u64 {
+    svm.get_sysvar::<Clock>().slot
+}
+
+pub fn create_mint(svm: &mut LiteSVM, authority: &Keypair, decimals: u8) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = svm.minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN);
+    let ixs = [
+        system_instruction::create_account(
+            &authority.pubkey(),
+            &mint.pubkey(),
+            rent,
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &authority.pubkey(),
+            None,
+            decimals,
+        )
+        .unwrap(),
+    ];
+    send(svm, &ixs, authority, &[&mint]).unwrap();
+    mint.pubkey()
+}
+
+// Creates owner's ATA for mint and funds it with amount
+pub fn create_ata(
+    svm: &mut LiteSVM,
+    mint_authority: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let ata = get_associated_token_address(owner, mint);
+    let mut ixs = vec![create_associated_token_account(
+        &mint_authority.pubkey(),
+        owner,
+        mint,
+        &spl_token::ID,
+    )];
+    if amount > 0 {
+        ixs.push(
+            spl_token::instruction::mint_to(
+                &spl_token::ID,
+                mint,
+                &ata,
+                &mint_authority.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        );
+    }
+    send(svm, &ixs, mint_authority, &[]).unwrap();
+    ata
+}
+
+// Mints amount more into an existing token account
+pub fn mint_to(
+    svm: &mut LiteSVM,
+    mint_authority: &Keypair,
+    mint: &Pubkey,
+    ata: &Pubkey,
+    amount: u64,
+) {
+    let ix = spl_token::instruction::mint_to(
+        &spl_token::ID,
+        mint,
+        ata,
+        &mint_authority.pubkey(),
+        &[],
+        amount,
+    )
+    .unwrap();
+    send(svm, &[ix], mint_authority, &[]).unwrap();
+}
+
+pub fn token_balance(svm: &LiteSVM, address: &Pubkey) -> u64 {
+    svm.get_account(address)
+        .map(|account| {
+            spl_token::state::Account::unpack(&account.data)
+                .unwrap()
+                .amount
+        })
+        .unwrap_or(0)
+}
+
+pub fn fetch<T: AccountDeserialize>(svm: &LiteSVM, address: &Pubkey) -> T {
+    let account = svm.get_account(address).expect("account not found");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+// Decodes the pool's accounts into the SDK's quoting view
+pub fn pool_state(svm: &LiteSVM, keys: &PoolKeys) -> PoolState {
+    let data = |address: &Pubkey| svm.get_account(address).unwrap().data;
+    let mint_x = svm.get_account(&keys.mint_x).unwrap();
+    let mint_y = svm.get_account(&keys.mint_y).unwrap();
+    PoolState::decode(
+        &data(&keys.config),
+        &data(&keys.pooldata),
+        &data(&keys.vault_x),
+        &data(&keys.vault_y),
+        &data(&keys.mint_lp),
+        (&mint_x.owner, &mint_x.data),
+        (&mint_y.owner, &mint_y.data),
+    )
+    .unwrap()
+}
+
+// Asserts the transaction failed with the given Anchor error code
+pub fn assert_error(res: TransactionResult, error: impl Into<u32>) {
+    let code = error.into();
+    match res {
+        Ok(_) => panic!("expected error {code}, transaction succeeded"),
+        Err(failed) => assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::Custom(code)),
+            "logs: {:#?}",
+            failed.meta.logs
+        ),
+    }
+}
+
+// Slot the pool fixtures start from, so tests can go back a few slots
+pub const START_SLOT: u64 = 100;
+
+// How setup_pool creates and funds a fairswap pool
+pub struct PoolParams {
+    pub fee: u16,
+    pub window_slots: u64,
+    pub curve: CurveType,
+    pub amp: u64,
+    pub weights: (u8, u8),
+    // first deposit of X and Y, the admin is funded with exactly this
+    pub deposit: (u64, u64),
+    pub batch_auction: bool,
+}
+
+impl Default for PoolParams {
+    fn default() -> Self {
+        Self {
+            fee: 30,
+            window_slots: 10,
+            curve: CurveType::ConstantProduct,
+            amp: 0,
+            weights: (50, 50),
+            deposit: (2_000_000, 2_000_000),
+            batch_auction: false,
+        }
+    }
+}
+
+// Pool over two fresh 6 decimal mints at START_SLOT, initialized and funded by the
+// returned admin, who is also the mint authority for funding traders
+pub fn setup_pool(params: PoolParams) -> (LiteSVM, PoolKeys, Keypair) {
+    let mut svm = setup();
+    svm.warp_to_slot(START_SLOT);
+    let admin = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    let (deposit_x, deposit_y) = params.deposit;
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), deposit_x);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), deposit_y);
+
+    let curve = match params.curve {
+        CurveType::ConstantProduct => Curve::ConstantProduct,
+        CurveType::StableSwap => Curve::StableSwap { amp: params.amp },
+        CurveType::Weighted => Curve::Weighted {
+            weight_in: params.weights.0,
+            weight_out: params.weights.1,
+        },
+    };
+    let liquidity = curve.initial_liquidity(deposit_x, deposit_y).unwrap();
+
+    let keys = PoolKeys::new(mint_x, mint_y, 1);
+    let admin_key = admin.pubkey();
+    let mut ixs = vec![
+        keys.initialize_with_curve(
+            &admin_key,
+            params.fee,
+            0,
+            params.window_slots,
+            admin_key,
+            SurplusPolicy::Lp,
+            params.curve,
+            params.amp,
+            params.weights.0,
+            params.weights.1,
+        ),
+        keys.deposit(
+            &admin_key,
+            liquidity - PoolData::MINIMUM_LIQUIDITY,
+            deposit_x,
+            deposit_y,
+            Deadline::Slot(u64::MAX),
+        ),
+    ];
+    if params.batch_auction {
+        ixs.push(keys.set_batch_auction(&admin_key, true));
+    }
+    for ix in ixs {
+        send(&mut svm, &[ix], &admin, &[]).unwrap();
+    }
+    (svm, keys, admin)
+}
+
+// Every fairswap PDA of one pool
+pub struct FairswapPool {
+    pub seed: u64,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub config: Pubkey,
+    pub pooldata: Pubkey,
+    pub auth: Pubkey,
+    pub mint_lp: Pubkey,
+    pub locked_lp: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+}
+
+impl FairswapPool {
+    pub fn new(mint_x: Pubkey, mint_y: Pubkey, seed: u64) -> Self {
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &fairswap::ID).0;
+        let config = pda(&[
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            &seed.to_le_bytes(),
+        ]);
+        Self {
+            seed,
+            mint_x,
+            mint_y,
+            config,
+            pooldata: pda(&[b"pooldata", config.as_ref()]),
+            auth: pda(&[b"auth", config.as_ref()]),
+            mint_lp: pda(&[b"mint_lp", config.as_ref()]),
+            locked_lp: pda(&[b"locked_lp", config.as_ref()]),
+            vault_x: pda(&[b"vault", config.as_ref(), mint_x.as_ref()]),
+            vault_y: pda(&[b"vault", config.as_ref(), mint_y.as_ref()]),
+        }
+    }
+
+    pub fn user_ata_lp(&self, user: &Pubkey) -> Pubkey {
+        get_associated_token_address(user, &self.mint_lp)
+    }
+
+    pub fn config(&self, svm: &LiteSVM) -> Config {
+        fetch(svm, &self.config)
+    }
+
+    pub fn pooldata(&self, svm: &LiteSVM) -> PoolData {
+        fetch(svm, &self.pooldata)
+    }
+
+    pub fn initialize_ix(&self, admin: &Pubkey, fee: u16, window_slots: u64) -> Instruction {
+        Instruction {
+            program_id: fairswap::ID,
+            accounts: fairswap::accounts::Initialize {
+                admin: *admin,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                config: self.config,
+                pooldata: self.pooldata,
+                auth: self.auth,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                token_program_x: spl_token::ID,
+                token_program_y: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: fairswap::instruction::Initialize {
+                seed: self.seed,
+                fee,
+                protocol_fee_bps: 0,
+                window_slots,
+                treasury: *admin,
+                surplus_policy: SurplusPolicy::Lp,
+                curve: CurveType::ConstantProduct,
+                amp: 0,
+                weight_x: 50,
+                weight_y: 50,
+            }
+            .data(),
+        }
+    }
+
+    pub fn deposit_ix(&self, user: &Pubkey, amount: u64, max_x: u64, max_y: u64) -> Instruction {
+        Instruction {
+            program_id: fairswap::ID,
+            accounts: fairswap::accounts::Deposit {
+                user: *user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                auth: self.auth,
+                user_ata_x: get_associated_token_address(user, &self.mint_x),
+                user_ata_y: get_associated_token_address(user, &self.mint_y),
+                mint_lp: self.mint_lp,
+                user_ata_lp: self.user_ata_lp(user),
+                locked_lp: self.locked_lp,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                config: self.config,
+                pooldata: self.pooldata,
+                token_program_x: spl_token::ID,
+                token_program_y: spl_token::ID,
+                token_program_lp: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: fairswap::instruction::Deposit {
+                amount,
+                max_x,
+                max_y,
+                deadline: Deadline::Slot(u64::MAX),
+            }
+            .data(),
+        }
+    }
+
+    pub fn withdraw_ix(&self, user: &Pubkey, amount: u64, min_x: u64, min_y: u64) -> Instruction {
+        Instruction {
+            program_id: fairswap::ID,
+            accounts: fairswap::accounts::Withdraw {
+                user: *user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                auth: self.auth,
+                user_ata_x: get_associated_token_address(user, &self.mint_x),
+                user_ata_y: get_associated_token_address(user, &self.mint_y),
+                user_ata_lp: self.user_ata_lp(user),
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                mint_lp: self.mint_lp,
+                config: self.config,
+                pooldata: self.pooldata,
+                token_program_x: spl_token::ID,
+                token_program_y: spl_token::ID,
+                token_program_lp: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: fairswap::instruction::Withdraw {
+                amount,
+                min_x,
+                min_y,
+                deadline: Deadline::Slot(u64::MAX),
+            }
+            .data(),
+        }
+    }
+
+    pub fn swap_ix(
+        &self,
+        user: &Pubkey,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        deadline: Deadline,
+    ) -> Instruction {
+        Instruction {
+            program_id: fairswap::ID,
+            accounts: fairswap::accounts::Swap {
+                user: *user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                auth: self.auth,
+                user_ata_x: get_associated_token_address(user, &self.mint_x),
+                user_ata_y: get_associated_token_address(user, &self.mint_y),
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                mint_lp: self.mint_lp,
+                config: self.config,
+                pooldata: self.pooldata,
+                token_program_x: spl_token::ID,
+                token_program_y: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: fairswap::instruction::Swap {
+                mint_deposit,
+                amount_in,
+                amount_out_min,
+                deadline,
+            }
+            .data(),
+        }
+    }
+}
+
+// Every simple_amm PDA and vault of one pool, the auth PDA is shared by all pools
+pub struct SimpleAmmPool {
+    pub seed: u64,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub config: Pubkey,
+    pub auth: Pubkey,
+    pub mint_lp: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+}
+
+impl SimpleAmmPool {
+    // simple_amm checks expirations against the unix timestamp
+    pub const EXPIRATION: i64 = i64::MAX;
+
+    pub fn new(mint_x: Pubkey, mint_y: Pubkey, seed: u64) -> Self {
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &simple_amm::ID).0;
+        let config = pda(&[b"config", &seed.to_le_bytes()]);
+        let auth = pda(&[b"auth"]);
+        Self {
+            seed,
+            mint_x,
+            mint_y,
+            config,
+            auth,
+            mint_lp: pda(&[b"lp", config.as_ref()]),
+            vault_x: get_associated_token_address(&auth, &mint_x),
+            vault_y: get_associated_token_address(&auth, &mint_y),
+        }
+    }
+
+    pub fn initialize_ix(&self, initializer: &Pubkey, fee: u16) -> Instruction {
+        Instruction {
+            program_id: simple_amm::ID,
+            accounts: simple_amm::accounts::Initialize {
+                initializer: *initializer,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                auth: self.auth,
+                config: self.config,
+                token_program: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: simple_amm::instruction::Initialize {
+                seed: self.seed,
+                fee,
+                authority: Some(*initializer),
+            }
+            .data(),
+        }
+    }
+
+    pub fn deposit_ix(&self, user: &Pubkey, amount: u64, max_x: u64, max_y: u64) -> Instruction {
+        Instruction {
+            program_id: simple_amm::ID,
+            accounts: simple_amm::accounts::Deposit {
+                user: *user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                user_x: get_associated_token_address(user, &self.mint_x),
+                user_y: get_associated_token_address(user, &self.mint_y),
+                user_lp: get_associated_token_address(user, &self.mint_lp),
+                auth: self.auth,
+                config: self.config,
+                token_program: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: simple_amm::instruction::Deposit {
+                amount,
+                max_x,
+                max_y,
+                expiration: Self::EXPIRATION,
+            }
+            .data(),
+        }
+    }
+
+    pub fn swap_ix(&self, user: &Pubkey, is_x: bool, amount: u64, min: u64) -> Instruction {
+        Instruction {
+            program_id: simple_amm::ID,
+            accounts: simple_amm::accounts::Swap {
+                user: *user,
+                mint_x: self.mint_x,
+                mint_y: self.mint_y,
+                mint_lp: self.mint_lp,
+                vault_x: self.vault_x,
+                vault_y: self.vault_y,
+                user_x: get_associated_token_address(user, &self.mint_x),
+                user_y: get_associated_token_address(user, &self.mint_y),
+                auth: self.auth,
+                config: self.config,
+                token_program: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: simple_amm::instruction::Swap {
+                is_x,
+                amount,
+                min,
+                expiration: Self::EXPIRATION,
+            }
+            .data(),
+        }
+    }
+}
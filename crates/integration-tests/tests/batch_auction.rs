@@ -0,0 +1,287 @@
+// Batch-auction pools queue swaps per slot and clear them at one price, so the order in
+// which orders land inside the slot must not change what anyone receives.
+use fairswap::errors::AmmError;
+use fairswap::states::{Batch, Deadline, PoolData};
+use fairswap_sdk::math::{batch, curve};
+use fairswap_sdk::pda::find_batch;
+use fairswap_sdk::PoolKeys;
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::signature::{Keypair, Signer};
+
+const DEPOSIT: u64 = 2_000_000;
+const FEE: u16 = 30;
+
+struct Traders {
+    alice: Keypair,
+    bob: Keypair,
+    carol: Keypair,
+}
+
+// Batch-auction pool of 2e6 X and 2e6 Y. Alice and Bob hold X, Carol holds Y.
+fn setup_batch_pool() -> (LiteSVM, PoolKeys, Keypair, Traders) {
+    let (mut svm, keys, admin) = setup_pool(PoolParams {
+        fee: FEE,
+        deposit: (DEPOSIT, DEPOSIT),
+        batch_auction: true,
+        ..Default::default()
+    });
+    let traders = Traders {
+        alice: new_user(&mut svm),
+        bob: new_user(&mut svm),
+        carol: new_user(&mut svm),
+    };
+    for (trader, x, y) in [
+        (&traders.alice, 100_000, 0),
+        (&traders.bob, 100_000, 0),
+        (&traders.carol, 0, 100_000),
+    ] {
+        create_ata(&mut svm, &admin, &keys.mint_x, &trader.pubkey(), x);
+        create_ata(&mut svm, &admin, &keys.mint_y, &trader.pubkey(), y);
+    }
+    (svm, keys, admin, traders)
+}
+
+fn place(svm: &mut LiteSVM, keys: &PoolKeys, trader: &Keypair, slot: u64, x_in: bool, amount: u64) {
+    let mint = match x_in {
+        true => keys.mint_x,
+        false => keys.mint_y,
+    };
+    send(
+        svm,
+        &[keys.place_batch_order(&trader.pubkey(), slot, mint, amount, 1)],
+        trader,
+        &[],
+    )
+    .unwrap();
+}
+
+fn claim(svm: &mut LiteSVM, keys: &PoolKeys, trader: &Keypair, slot: u64, payer: &Keypair) {
+    send(
+        svm,
+        &[keys.claim_batch_order(&trader.pubkey(), slot, &payer.pubkey())],
+        trader,
+        &[],
+    )
+    .unwrap();
+}
+
+// Places the orders in the given sequence, clears the batch and returns what each
+// trader was paid, as (alice Y, bob Y, carol X)
+fn run_batch(sequence: [usize; 3]) -> (u64, u64, u64) {
+    let (mut svm, keys, _, traders) = setup_batch_pool();
+    let slot = START_SLOT + 1;
+    let orders = [
+        (&traders.alice, true, 60_000),
+        (&traders.bob, true, 20_000),
+        (&traders.carol, false, 50_000),
+    ];
+    for index in sequence {
+        let (trader, x_in, amount) = orders[index];
+        place(&mut svm, &keys, trader, slot, x_in, amount);
+    }
+
+    svm.warp_to_slot(slot + 1);
+    send(&mut svm, &[keys.clear_batch(slot)], &traders.alice, &[]).unwrap();
+    let payer = orders[sequence[0]].0;
+    for index in sequence {
+        claim(&mut svm, &keys, orders[index].0, slot, payer);
+    }
+    (
+        token_balance(&svm, &keys.ata_y(&traders.alice.pubkey())),
+        token_balance(&svm, &keys.ata_y(&traders.bob.pubkey())),
+        token_balance(&svm, &keys.ata_x(&traders.carol.pubkey())),
+    )
+}
+
+#[test]
+fn order_within_the_slot_does_not_matter() {
+    let payouts = run_batch([0, 1, 2]);
+    assert_eq!(run_batch([2, 1, 0]), payouts);
+    assert_eq!(run_batch([1, 2, 0]), payouts);
+
+    // one price for the whole batch, fees taken from every input first
+    let net = |amount| amount - curve::fee_amount(amount, FEE).unwrap();
+    let (alice, bob, carol) = (net(60_000), net(20_000), net(50_000));
+    let fee_x = 80_000 - alice - bob;
+    let fee_y = 50_000 - carol;
+    let clearing = batch::clear(DEPOSIT + fee_x, DEPOSIT + fee_y, alice + bob, carol).unwrap();
+    assert_eq!(
+        payouts,
+        (
+            batch::pro_rata(alice, alice + bob, clearing.out_y).unwrap(),
+            batch::pro_rata(bob, alice + bob, clearing.out_y).unwrap(),
+            clearing.out_x,
+        )
+    );
+}
+
+#[test]
+fn clear_and_claim_settle_the_escrow() {
+    let (mut svm, keys, _, traders) = setup_batch_pool();
+    let slot = START_SLOT + 2;
+    place(&mut svm, &keys, &traders.alice, slot, true, 60_000);
+    place(&mut svm, &keys, &traders.carol, slot, false, 50_000);
+    // topping up an order adds to it rather than opening a second one
+    place(&mut svm, &keys, &traders.alice, slot, true, 10_000);
+
+    let batch_address = find_batch(&keys.config, slot).0;
+    let batch: Batch = fetch(&svm, &batch_address);
+    assert_eq!(batch.open_orders, 2);
+    assert_eq!(batch.payer, traders.alice.pubkey());
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.batch_x, 70_000);
+    assert_eq!(pooldata.batch_y, 50_000);
+
+    // still accepting orders
+    svm.warp_to_slot(slot);
+    let res = send(&mut svm, &[keys.clear_batch(slot)], &traders.bob, &[]);
+    assert_error(res, AmmError::BatchOpen);
+
+    svm.warp_to_slot(slot + 1);
+    let res = send(
+        &mut svm,
+        &[keys.claim_batch_order(&traders.carol.pubkey(), slot, &traders.alice.pubkey())],
+        &traders.carol,
+        &[],
+    );
+    assert_error(res, AmmError::BatchNotCleared);
+
+    send(&mut svm, &[keys.clear_batch(slot)], &traders.bob, &[]).unwrap();
+    let res = send(&mut svm, &[keys.clear_batch(slot)], &traders.carol, &[]);
+    assert_error(res, AmmError::BatchCleared);
+
+    let batch: Batch = fetch(&svm, &batch_address);
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert!(batch.cleared);
+    assert_eq!(pooldata.batch_x, batch.amount_out_x);
+    assert_eq!(pooldata.batch_y, batch.amount_out_y);
+
+    claim(&mut svm, &keys, &traders.carol, slot, &traders.alice);
+    claim(&mut svm, &keys, &traders.alice, slot, &traders.alice);
+    assert_eq!(
+        token_balance(&svm, &keys.ata_x(&traders.carol.pubkey())),
+        batch.amount_out_x
+    );
+    assert_eq!(
+        token_balance(&svm, &keys.ata_y(&traders.alice.pubkey())),
+        batch.amount_out_y
+    );
+
+    // the last claim closes the batch and leaves nothing held back in the vaults
+    assert!(svm.get_account(&batch_address).is_none());
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!((pooldata.batch_x, pooldata.batch_y), (0, 0));
+    let vault_x = token_balance(&svm, &keys.vault_x);
+    let vault_y = token_balance(&svm, &keys.vault_y);
+    assert_eq!(
+        pooldata.reserves(vault_x, vault_y).unwrap(),
+        (vault_x, vault_y)
+    );
+    assert!(vault_x as u128 * vault_y as u128 >= DEPOSIT as u128 * DEPOSIT as u128);
+}
+
+#[test]
+fn order_below_its_minimum_is_refunded() {
+    let (mut svm, keys, _, traders) = setup_batch_pool();
+    let slot = START_SLOT + 1;
+    // Alice asks for 1:1, which the fee alone already rules out
+    send(
+        &mut svm,
+        &[keys.place_batch_order(&traders.alice.pubkey(), slot, keys.mint_x, 60_000, 60_000)],
+        &traders.alice,
+        &[],
+    )
+    .unwrap();
+    place(&mut svm, &keys, &traders.bob, slot, true, 20_000);
+    place(&mut svm, &keys, &traders.carol, slot, false, 50_000);
+
+    svm.warp_to_slot(slot + 1);
+    send(&mut svm, &[keys.clear_batch(slot)], &traders.bob, &[]).unwrap();
+    let batch: Batch = fetch(&svm, &find_batch(&keys.config, slot).0);
+    for trader in [&traders.alice, &traders.bob, &traders.carol] {
+        claim(&mut svm, &keys, trader, slot, &traders.alice);
+    }
+
+    // Alice gets her input back less the swap fee, the others settle at the clearing price
+    let net = |amount| amount - curve::fee_amount(amount, FEE).unwrap();
+    let (alice, bob) = (net(60_000), net(20_000));
+    let alice_share = batch::pro_rata(alice, alice + bob, batch.amount_out_y).unwrap();
+    assert!(alice_share < 60_000);
+    assert_eq!(
+        token_balance(&svm, &keys.ata_x(&traders.alice.pubkey())),
+        100_000 - 60_000 + alice
+    );
+    assert_eq!(token_balance(&svm, &keys.ata_y(&traders.alice.pubkey())), 0);
+    assert_eq!(
+        token_balance(&svm, &keys.ata_y(&traders.bob.pubkey())),
+        batch::pro_rata(bob, alice + bob, batch.amount_out_y).unwrap()
+    );
+    assert_eq!(
+        token_balance(&svm, &keys.ata_x(&traders.carol.pubkey())),
+        batch.amount_out_x
+    );
+
+    // the missed share went back to the reserves, nothing is left in escrow
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!((pooldata.batch_x, pooldata.batch_y), (0, 0));
+    let vault_x = token_balance(&svm, &keys.vault_x);
+    let vault_y = token_balance(&svm, &keys.vault_y);
+    assert_eq!(
+        pooldata.reserves(vault_x, vault_y).unwrap(),
+        (vault_x, vault_y)
+    );
+}
+
+#[test]
+fn batch_mode_replaces_direct_swaps() {
+    let (mut svm, keys, admin, traders) = setup_batch_pool();
+    let alice = traders.alice.pubkey();
+
+    let res = send(
+        &mut svm,
+        &[keys.swap(&alice, keys.mint_x, 10_000, 1, Deadline::Slot(u64::MAX))],
+        &traders.alice,
+        &[],
+    );
+    assert_error(res, AmmError::BatchAuctionOnly);
+
+    // slots already past and slots too far ahead are both refused
+    let res = send(
+        &mut svm,
+        &[keys.place_batch_order(&alice, START_SLOT - 1, keys.mint_x, 10_000, 1)],
+        &traders.alice,
+        &[],
+    );
+    assert_error(res, AmmError::InvalidBatchSlot);
+    let far = START_SLOT + Batch::MAX_SLOTS_AHEAD + 1;
+    let res = send(
+        &mut svm,
+        &[keys.place_batch_order(&alice, far, keys.mint_x, 10_000, 1)],
+        &traders.alice,
+        &[],
+    );
+    assert_error(res, AmmError::InvalidBatchSlot);
+
+    send(
+        &mut svm,
+        &[keys.set_batch_auction(&admin.pubkey(), false)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    let res = send(
+        &mut svm,
+        &[keys.place_batch_order(&alice, START_SLOT, keys.mint_x, 10_000, 1)],
+        &traders.alice,
+        &[],
+    );
+    assert_error(res, AmmError::BatchAuctionDisabled);
+    send(
+        &mut svm,
+        &[keys.swap(&alice, keys.mint_x, 10_000, 1, Deadline::Slot(u64::MAX))],
+        &traders.alice,
+        &[],
+    )
+    .unwrap();
+}
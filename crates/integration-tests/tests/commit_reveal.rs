@@ -0,0 +1,145 @@
+// Commit-reveal swaps escrow the input behind a hash and execute it slots later, at
+// the same price a direct swap would get in the reveal slot.
+use fairswap::errors::AmmError;
+use fairswap::states::{PoolData, SwapCommitment};
+use fairswap_sdk::pda::find_commitment;
+use fairswap_sdk::PoolKeys;
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const DEPOSIT: u64 = 2_000_000;
+const BALANCE: u64 = 100_000;
+const SALT: [u8; 32] = [7; 32];
+
+// Pool of 2e6 X and 2e6 Y and a trader holding both tokens
+fn setup_trader_pool() -> (LiteSVM, PoolKeys, Keypair) {
+    let (mut svm, keys, admin) = setup_pool(PoolParams {
+        deposit: (DEPOSIT, DEPOSIT),
+        ..Default::default()
+    });
+    let trader = new_user(&mut svm);
+    create_ata(&mut svm, &admin, &keys.mint_x, &trader.pubkey(), BALANCE);
+    create_ata(&mut svm, &admin, &keys.mint_y, &trader.pubkey(), BALANCE);
+    (svm, keys, trader)
+}
+
+fn balances(svm: &LiteSVM, keys: &PoolKeys, trader: &Pubkey) -> (u64, u64) {
+    (
+        token_balance(svm, &keys.ata_x(trader)),
+        token_balance(svm, &keys.ata_y(trader)),
+    )
+}
+
+#[test]
+fn reveal_swaps_at_the_direct_swap_price() {
+    let (mut svm, keys, trader) = setup_trader_pool();
+    let user = trader.pubkey();
+    let hash = SwapCommitment::hash(&keys.mint_x, 40_000, 1, &SALT);
+
+    // escrowing Y as well hides that the swap sells X
+    send(
+        &mut svm,
+        &[keys.commit_swap(&user, hash, 50_000, 30_000)],
+        &trader,
+        &[],
+    )
+    .unwrap();
+    let commitment = find_commitment(&keys.config, &user, &hash).0;
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(
+        (pooldata.committed_x, pooldata.committed_y),
+        (50_000, 30_000)
+    );
+    // the escrow is not liquidity
+    let reserves = pooldata
+        .reserves(
+            token_balance(&svm, &keys.vault_x),
+            token_balance(&svm, &keys.vault_y),
+        )
+        .unwrap();
+    assert_eq!(reserves, (DEPOSIT, DEPOSIT));
+
+    // too early, the reveal could still land in the same block as the commit
+    svm.warp_to_slot(START_SLOT + SwapCommitment::MIN_REVEAL_SLOTS - 1);
+    let reveal = keys.reveal_swap(&user, keys.mint_x, 40_000, 1, SALT);
+    let res = send(&mut svm, std::slice::from_ref(&reveal), &trader, &[]);
+    assert_error(res, AmmError::RevealNotAllowed);
+
+    // parameters that don't hash to the commitment are refused
+    svm.warp_to_slot(START_SLOT + SwapCommitment::MIN_REVEAL_SLOTS);
+    let mut wrong = keys.reveal_swap(&user, keys.mint_x, 50_000, 1, SALT);
+    wrong.accounts[10].pubkey = commitment;
+    let res = send(&mut svm, &[wrong], &trader, &[]);
+    assert_error(res, AmmError::CommitmentMismatch);
+
+    // the SDK prices a direct swap exactly like the program, see sdk_quote.rs
+    let clock = svm.get_sysvar::<Clock>();
+    let direct_out = pool_state(&svm, &keys)
+        .swap(keys.mint_x, 40_000, 1, &clock)
+        .unwrap()
+        .amount_out;
+
+    send(&mut svm, &[reveal], &trader, &[]).unwrap();
+    // the unused escrow comes back along with the output
+    assert_eq!(
+        balances(&svm, &keys, &user),
+        (BALANCE - 40_000, BALANCE + direct_out)
+    );
+    assert!(svm.get_account(&commitment).is_none());
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!((pooldata.committed_x, pooldata.committed_y), (0, 0));
+    assert_eq!(token_balance(&svm, &keys.vault_x), DEPOSIT + 40_000,);
+}
+
+#[test]
+fn unrevealed_commitment_is_refunded_after_the_window() {
+    let (mut svm, keys, trader) = setup_trader_pool();
+    let user = trader.pubkey();
+    let hash = SwapCommitment::hash(&keys.mint_y, 20_000, 1, &SALT);
+    send(
+        &mut svm,
+        &[keys.commit_swap(&user, hash, 0, 20_000)],
+        &trader,
+        &[],
+    )
+    .unwrap();
+
+    let last_reveal_slot = START_SLOT + SwapCommitment::MAX_REVEAL_SLOTS;
+    svm.warp_to_slot(last_reveal_slot);
+    let res = send(
+        &mut svm,
+        &[keys.refund_commitment(&user, hash)],
+        &trader,
+        &[],
+    );
+    assert_error(res, AmmError::CommitmentNotExpired);
+
+    svm.warp_to_slot(last_reveal_slot + 1);
+    let res = send(
+        &mut svm,
+        &[keys.reveal_swap(&user, keys.mint_y, 20_000, 1, SALT)],
+        &trader,
+        &[],
+    );
+    assert_error(res, AmmError::RevealNotAllowed);
+
+    send(
+        &mut svm,
+        &[keys.refund_commitment(&user, hash)],
+        &trader,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(balances(&svm, &keys, &user), (BALANCE, BALANCE));
+    assert!(svm
+        .get_account(&find_commitment(&keys.config, &user, &hash).0)
+        .is_none());
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!((pooldata.committed_x, pooldata.committed_y), (0, 0));
+    assert_eq!(token_balance(&svm, &keys.vault_y), DEPOSIT);
+}
@@ -0,0 +1,164 @@
+// Same scenario as tests/fairVersusNormal.ts: two identical pools, one per program.
+use anchor_spl::associated_token::get_associated_token_address;
+use fairswap::states::Deadline;
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::signature::{Keypair, Signer};
+
+const DEPOSIT: u64 = 2_000_000;
+const SEED: u64 = 42;
+
+struct Pools {
+    fairswap: FairswapPool,
+    normal: SimpleAmmPool,
+    user1: Keypair,
+    user2: Keypair,
+}
+
+fn setup_pools(svm: &mut LiteSVM) -> Pools {
+    let initializer = new_user(svm);
+    let user1 = new_user(svm);
+    let user2 = new_user(svm);
+    let mint_x = create_mint(svm, &initializer, 6);
+    let mint_y = create_mint(svm, &initializer, 6);
+    create_ata(
+        svm,
+        &initializer,
+        &mint_x,
+        &initializer.pubkey(),
+        1_000_000_000,
+    );
+    create_ata(
+        svm,
+        &initializer,
+        &mint_y,
+        &initializer.pubkey(),
+        1_000_000_000,
+    );
+    for user in [&user1, &user2] {
+        create_ata(svm, &initializer, &mint_x, &user.pubkey(), 100_000);
+        create_ata(svm, &initializer, &mint_y, &user.pubkey(), 0);
+    }
+
+    let fairswap = FairswapPool::new(mint_x, mint_y, SEED);
+    let normal = SimpleAmmPool::new(mint_x, mint_y, SEED);
+    send(
+        svm,
+        &[
+            fairswap.initialize_ix(&initializer.pubkey(), 0, 1),
+            normal.initialize_ix(&initializer.pubkey(), 0),
+        ],
+        &initializer,
+        &[],
+    )
+    .unwrap();
+    // fairswap locks MINIMUM_LIQUIDITY out of the first deposit's LP
+    send(
+        svm,
+        &[
+            fairswap.deposit_ix(&initializer.pubkey(), DEPOSIT - 1000, DEPOSIT, DEPOSIT),
+            normal.deposit_ix(&initializer.pubkey(), DEPOSIT, DEPOSIT, DEPOSIT),
+        ],
+        &initializer,
+        &[],
+    )
+    .unwrap();
+
+    Pools {
+        fairswap,
+        normal,
+        user1,
+        user2,
+    }
+}
+
+fn balances(svm: &LiteSVM, pools: &Pools, user: &Keypair) -> (u64, u64) {
+    let ata = |mint| get_associated_token_address(&user.pubkey(), mint);
+    (
+        token_balance(svm, &ata(&pools.fairswap.mint_x)),
+        token_balance(svm, &ata(&pools.fairswap.mint_y)),
+    )
+}
+
+// user1 swaps on fairswap and user2 on simple_amm, 2e4 X for Y each
+fn swap_x_for_y(svm: &mut LiteSVM, pools: &Pools) {
+    let (user1, user2) = (&pools.user1, &pools.user2);
+    send(
+        svm,
+        &[pools.fairswap.swap_ix(
+            &user1.pubkey(),
+            pools.fairswap.mint_x,
+            20_000,
+            10_000,
+            Deadline::Slot(u64::MAX),
+        )],
+        user1,
+        &[],
+    )
+    .unwrap();
+    send(
+        svm,
+        &[pools.normal.swap_ix(&user2.pubkey(), true, 20_000, 10_000)],
+        user2,
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn single_swap_pays_the_same_on_both_amms() {
+    let mut svm = setup();
+    let pools = setup_pools(&mut svm);
+    swap_x_for_y(&mut svm, &pools);
+
+    let (user1, user2) = (&pools.user1, &pools.user2);
+    assert_eq!(balances(&svm, &pools, user1), balances(&svm, &pools, user2));
+}
+
+#[test]
+fn self_mev_is_unprofitable_on_fairswap() {
+    let mut svm = setup();
+    let pools = setup_pools(&mut svm);
+    // leaves both traders holding the Y they sell back below
+    swap_x_for_y(&mut svm, &pools);
+
+    let (user1, user2) = (&pools.user1, &pools.user2);
+    let deadline = Deadline::Slot(u64::MAX);
+    let (mint_x, mint_y) = (pools.fairswap.mint_x, pools.fairswap.mint_y);
+
+    send(
+        &mut svm,
+        &[
+            pools
+                .fairswap
+                .swap_ix(&user1.pubkey(), mint_x, 15_000, 1_000, deadline),
+            pools
+                .fairswap
+                .swap_ix(&user1.pubkey(), mint_x, 15_000, 1_000, deadline),
+            pools
+                .fairswap
+                .swap_ix(&user1.pubkey(), mint_y, 30_000, 1_000, deadline),
+        ],
+        user1,
+        &[],
+    )
+    .unwrap();
+    send(
+        &mut svm,
+        &[
+            pools.normal.swap_ix(&user2.pubkey(), true, 15_000, 1_000),
+            pools.normal.swap_ix(&user2.pubkey(), true, 15_000, 1_000),
+            pools.normal.swap_ix(&user2.pubkey(), false, 30_000, 1_000),
+        ],
+        user2,
+        &[],
+    )
+    .unwrap();
+
+    let (fair_x, _) = balances(&svm, &pools, user1);
+    let (normal_x, _) = balances(&svm, &pools, user2);
+    assert!(
+        fair_x < normal_x,
+        "fairswap trader kept {fair_x} X, simple_amm trader {normal_x} X"
+    );
+}
@@ -0,0 +1,221 @@
+use anchor_spl::token::spl_token;
+use fairswap::{errors::AmmError, states::Deadline, states::PoolData};
+use integration_tests::*;
+use solana_sdk::signature::Signer;
+
+const DEPOSIT: u64 = 2_000_000;
+
+#[test]
+fn deposit_locks_minimum_liquidity_and_seeds_window() {
+    let mut svm = setup();
+    svm.warp_to_slot(50);
+    let admin = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), 1_000_000_000);
+
+    let pool = FairswapPool::new(mint_x, mint_y, 1);
+    send(
+        &mut svm,
+        &[pool.initialize_ix(&admin.pubkey(), 0, 10)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    // asking for the full sqrt(x*y) fails, MINIMUM_LIQUIDITY of it is never minted to the user
+    let res = send(
+        &mut svm,
+        &[pool.deposit_ix(&admin.pubkey(), DEPOSIT, DEPOSIT, DEPOSIT)],
+        &admin,
+        &[],
+    );
+    assert_error(res, AmmError::SlippageExceeded);
+
+    let lp_amount = DEPOSIT - PoolData::MINIMUM_LIQUIDITY;
+    send(
+        &mut svm,
+        &[pool.deposit_ix(&admin.pubkey(), lp_amount, DEPOSIT, DEPOSIT)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&svm, &pool.user_ata_lp(&admin.pubkey())),
+        lp_amount
+    );
+    assert_eq!(
+        token_balance(&svm, &pool.locked_lp),
+        PoolData::MINIMUM_LIQUIDITY
+    );
+    assert_eq!(token_balance(&svm, &pool.vault_x), DEPOSIT);
+    assert_eq!(token_balance(&svm, &pool.vault_y), DEPOSIT);
+
+    let pooldata = pool.pooldata(&svm);
+    assert_eq!(pooldata.last_slot, 50);
+    assert_eq!(pooldata.buying_x_high, Some(1_000_000));
+    assert_eq!(pooldata.buying_y_high, Some(1_000_000));
+}
+
+#[test]
+fn swap_and_withdraw() {
+    let mut svm = setup();
+    let admin = new_user(&mut svm);
+    let user = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), 1_000_000_000);
+    let user_x = create_ata(&mut svm, &admin, &mint_x, &user.pubkey(), 100_000);
+    let user_y = create_ata(&mut svm, &admin, &mint_y, &user.pubkey(), 0);
+
+    let pool = FairswapPool::new(mint_x, mint_y, 2);
+    send(
+        &mut svm,
+        &[pool.initialize_ix(&admin.pubkey(), 0, 10)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    let lp_amount = DEPOSIT - PoolData::MINIMUM_LIQUIDITY;
+    send(
+        &mut svm,
+        &[pool.deposit_ix(&admin.pubkey(), lp_amount, DEPOSIT, DEPOSIT)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    // the first swap of the window is not clamped, it pays out the plain curve amount
+    send(
+        &mut svm,
+        &[pool.swap_ix(
+            &user.pubkey(),
+            mint_x,
+            20_000,
+            10_000,
+            Deadline::Slot(u64::MAX),
+        )],
+        &user,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(token_balance(&svm, &user_x), 80_000);
+    let amount_out = token_balance(&svm, &user_y);
+    assert!((19_800..=19_802).contains(&amount_out));
+    assert_eq!(token_balance(&svm, &pool.vault_x), DEPOSIT + 20_000);
+    assert_eq!(token_balance(&svm, &pool.vault_y), DEPOSIT - amount_out);
+
+    // withdrawing everything but the locked LP leaves the reserves in proportion
+    send(
+        &mut svm,
+        &[pool.withdraw_ix(&admin.pubkey(), lp_amount, 1, 1)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(token_balance(&svm, &pool.user_ata_lp(&admin.pubkey())), 0);
+    assert!(token_balance(&svm, &pool.vault_x) < 2_000);
+    assert!(token_balance(&svm, &pool.vault_y) < 2_000);
+}
+
+#[test]
+fn swap_fails_after_the_deadline() {
+    let mut svm = setup();
+    let admin = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), 1_000_000_000);
+
+    let pool = FairswapPool::new(mint_x, mint_y, 3);
+    send(
+        &mut svm,
+        &[pool.initialize_ix(&admin.pubkey(), 0, 10)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    send(
+        &mut svm,
+        &[pool.deposit_ix(
+            &admin.pubkey(),
+            DEPOSIT - PoolData::MINIMUM_LIQUIDITY,
+            DEPOSIT,
+            DEPOSIT,
+        )],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    svm.warp_to_slot(20);
+    let res = send(
+        &mut svm,
+        &[pool.swap_ix(&admin.pubkey(), mint_x, 20_000, 1, Deadline::Slot(19))],
+        &admin,
+        &[],
+    );
+    assert_error(res, AmmError::Expired);
+
+    // the deadline slot itself is still accepted
+    send(
+        &mut svm,
+        &[pool.swap_ix(&admin.pubkey(), mint_x, 20_000, 1, Deadline::Slot(20))],
+        &admin,
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn donation_before_the_first_deposit_goes_to_the_first_lp() {
+    let mut svm = setup();
+    let admin = new_user(&mut svm);
+    let griefer = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), 1_000_000_000);
+    let griefer_ata_x = create_ata(&mut svm, &admin, &mint_x, &griefer.pubkey(), 1);
+
+    let pool = FairswapPool::new(mint_x, mint_y, 4);
+    send(
+        &mut svm,
+        &[pool.initialize_ix(&admin.pubkey(), 0, 10)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    // one unit of dust lands in the empty pool before anyone deposits
+    let donation = spl_token::instruction::transfer(
+        &spl_token::ID,
+        &griefer_ata_x,
+        &pool.vault_x,
+        &griefer.pubkey(),
+        &[],
+        1,
+    )
+    .unwrap();
+    send(&mut svm, &[donation], &griefer, &[]).unwrap();
+
+    // sqrt((2e6 + 1) * 2e6) still rounds down to 2e6
+    let lp_amount = DEPOSIT - PoolData::MINIMUM_LIQUIDITY;
+    send(
+        &mut svm,
+        &[pool.deposit_ix(&admin.pubkey(), lp_amount, DEPOSIT, DEPOSIT)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        token_balance(&svm, &pool.user_ata_lp(&admin.pubkey())),
+        lp_amount
+    );
+    assert_eq!(token_balance(&svm, &pool.vault_x), DEPOSIT + 1);
+    assert_eq!(token_balance(&svm, &pool.vault_y), DEPOSIT);
+}
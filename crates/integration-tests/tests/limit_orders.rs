@@ -0,0 +1,167 @@
+// Resting limit orders are filled by keepers through the same curve and fairness clamp
+// as a swap, so a fill can't beat the window's high-water mark either.
+use fairswap::errors::AmmError;
+use fairswap::states::{Deadline, LimitOrder, PoolData};
+use fairswap_sdk::pda::find_limit_order;
+use fairswap_sdk::PoolKeys;
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const DEPOSIT: u64 = 2_000_000;
+const BALANCE: u64 = 200_000;
+const WINDOW_SLOTS: u64 = 10;
+const REWARD: u64 = 50_000;
+// 1.02 Y per X at the pool's precision of 6
+const MIN_RATIO: u128 = 1_020_000;
+
+struct Users {
+    // sells X through limit orders
+    alice: Keypair,
+    // fills them
+    keeper: Keypair,
+    // holds Y and moves the price
+    carol: Keypair,
+}
+
+fn setup_users_pool() -> (LiteSVM, PoolKeys, Users) {
+    let (mut svm, keys, admin) = setup_pool(PoolParams {
+        window_slots: WINDOW_SLOTS,
+        deposit: (DEPOSIT, DEPOSIT),
+        ..Default::default()
+    });
+    let users = Users {
+        alice: new_user(&mut svm),
+        keeper: new_user(&mut svm),
+        carol: new_user(&mut svm),
+    };
+    create_ata(
+        &mut svm,
+        &admin,
+        &keys.mint_x,
+        &users.alice.pubkey(),
+        BALANCE,
+    );
+    create_ata(
+        &mut svm,
+        &admin,
+        &keys.mint_y,
+        &users.carol.pubkey(),
+        BALANCE,
+    );
+    (svm, keys, users)
+}
+
+fn lamports(svm: &LiteSVM, address: &Pubkey) -> u64 {
+    svm.get_account(address)
+        .map_or(0, |account| account.lamports)
+}
+
+fn place(svm: &mut LiteSVM, keys: &PoolKeys, alice: &Keypair, id: u64) {
+    send(
+        svm,
+        &[keys.place_limit_order(&alice.pubkey(), id, keys.mint_x, 10_000, MIN_RATIO, REWARD)],
+        alice,
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn keeper_fills_once_the_pool_pays_the_limit() {
+    let (mut svm, keys, users) = setup_users_pool();
+    let alice = users.alice.pubkey();
+    let keeper = users.keeper.pubkey();
+    place(&mut svm, &keys, &users.alice, 1);
+    let order_address = find_limit_order(&keys.config, &alice, 1).0;
+    let order: LimitOrder = fetch(&svm, &order_address);
+    assert_eq!(order.amount_in, 10_000);
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.limit_orders_x, 10_000);
+
+    // the keeper signs but carol pays the transaction fees, so the keeper's balance
+    // only moves by the reward
+    let fill = keys.fill_limit_order(&keeper, &alice, 1);
+    let res = send(
+        &mut svm,
+        std::slice::from_ref(&fill),
+        &users.carol,
+        &[&users.keeper],
+    );
+    assert_error(res, AmmError::LimitNotReached);
+
+    // carol buys X and lifts its price well above the limit
+    send(
+        &mut svm,
+        &[keys.swap(
+            &users.carol.pubkey(),
+            keys.mint_y,
+            200_000,
+            1,
+            Deadline::Slot(u64::MAX),
+        )],
+        &users.carol,
+        &[],
+    )
+    .unwrap();
+
+    // the window still holds the price X was sold at when it opened, so the fill is
+    // clamped to it like any swap would be
+    let res = send(
+        &mut svm,
+        std::slice::from_ref(&fill),
+        &users.carol,
+        &[&users.keeper],
+    );
+    assert_error(res, AmmError::LimitNotReached);
+
+    svm.warp_to_slot(START_SLOT + WINDOW_SLOTS);
+    let order_lamports = svm.get_account(&order_address).unwrap().lamports;
+    let alice_lamports = lamports(&svm, &alice);
+    let keeper_lamports = lamports(&svm, &keeper);
+    send(&mut svm, &[fill], &users.carol, &[&users.keeper]).unwrap();
+
+    let amount_out = token_balance(&svm, &keys.ata_y(&alice));
+    assert!(amount_out as u128 * 1_000_000 >= 10_000 * MIN_RATIO);
+    assert_eq!(token_balance(&svm, &keys.ata_x(&alice)), BALANCE - 10_000);
+    // the keeper gets the reward, alice the rest of the order's lamports
+    assert_eq!(lamports(&svm, &keeper), keeper_lamports + REWARD);
+    assert_eq!(
+        lamports(&svm, &alice),
+        alice_lamports + order_lamports - REWARD
+    );
+    assert!(svm.get_account(&order_address).is_none());
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.limit_orders_x, 0);
+}
+
+#[test]
+fn cancel_returns_the_escrow_and_the_reward() {
+    let (mut svm, keys, users) = setup_users_pool();
+    let alice = users.alice.pubkey();
+    place(&mut svm, &keys, &users.alice, 1);
+    place(&mut svm, &keys, &users.alice, 2);
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.limit_orders_x, 20_000);
+
+    let order_address = find_limit_order(&keys.config, &alice, 2).0;
+    let order_lamports = svm.get_account(&order_address).unwrap().lamports;
+    let alice_lamports = lamports(&svm, &alice);
+    // carol pays the fees so alice's balance only moves by what the order held
+    send(
+        &mut svm,
+        &[keys.cancel_limit_order(&alice, 2)],
+        &users.carol,
+        &[&users.alice],
+    )
+    .unwrap();
+
+    assert_eq!(token_balance(&svm, &keys.ata_x(&alice)), BALANCE - 10_000);
+    assert_eq!(lamports(&svm, &alice), alice_lamports + order_lamports);
+    assert!(svm.get_account(&order_address).is_none());
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.limit_orders_x, 10_000);
+}
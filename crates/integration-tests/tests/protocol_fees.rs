@@ -0,0 +1,91 @@
+// Protocol fees can only be collected by the authority, so renouncing it must not strand
+// any: the accrued fees have to be collected first and none accrue afterwards.
+use fairswap::errors::AmmError;
+use fairswap::states::{Config, CurveType, Deadline, PoolData, SurplusPolicy};
+use fairswap_sdk::PoolKeys;
+use integration_tests::*;
+use solana_sdk::signature::{Keypair, Signer};
+
+const DEPOSIT: u64 = 100_000_000;
+const FEE: u16 = 100;
+const PROTOCOL_FEE_BPS: u16 = 5_000;
+
+#[test]
+fn renouncing_collects_and_stops_protocol_fees() {
+    let mut svm = setup();
+    let admin = new_user(&mut svm);
+    let trader = new_user(&mut svm);
+    let treasury = Keypair::new();
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), DEPOSIT);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), DEPOSIT);
+    create_ata(&mut svm, &admin, &mint_x, &trader.pubkey(), 2_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &trader.pubkey(), 0);
+
+    let keys = PoolKeys::new(mint_x, mint_y, 1);
+    let admin_key = admin.pubkey();
+    for ix in [
+        keys.initialize_with_curve(
+            &admin_key,
+            FEE,
+            PROTOCOL_FEE_BPS,
+            1,
+            treasury.pubkey(),
+            SurplusPolicy::Lp,
+            CurveType::ConstantProduct,
+            0,
+            50,
+            50,
+        ),
+        keys.deposit(
+            &admin_key,
+            DEPOSIT - PoolData::MINIMUM_LIQUIDITY,
+            DEPOSIT,
+            DEPOSIT,
+            Deadline::Slot(u64::MAX),
+        ),
+    ] {
+        send(&mut svm, &[ix], &admin, &[]).unwrap();
+    }
+
+    // 1% of 1e6 is charged, half of it is kept for the treasury
+    let swap = || {
+        keys.swap(
+            &trader.pubkey(),
+            mint_x,
+            1_000_000,
+            1,
+            Deadline::Slot(u64::MAX),
+        )
+    };
+    send(&mut svm, &[swap()], &trader, &[]).unwrap();
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.protocol_fees_x, 5_000);
+
+    let res = send(
+        &mut svm,
+        &[keys.renounce_authority(&admin_key)],
+        &admin,
+        &[],
+    );
+    assert_error(res, AmmError::ProtocolFeesPending);
+
+    for ix in [
+        keys.collect_protocol_fees(&admin_key, &treasury.pubkey()),
+        keys.renounce_authority(&admin_key),
+    ] {
+        send(&mut svm, &[ix], &admin, &[]).unwrap();
+    }
+    assert_eq!(token_balance(&svm, &keys.ata_x(&treasury.pubkey())), 5_000);
+    let config: Config = fetch(&svm, &keys.config);
+    assert_eq!(config.authority, None);
+    assert_eq!(config.protocol_fee_bps, 0);
+
+    // the whole fee now goes to the LPs, nothing is left waiting for a collector
+    svm.warp_to_slot(current_slot(&svm) + 10);
+    send(&mut svm, &[swap()], &trader, &[]).unwrap();
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.protocol_fees_x, 0);
+    assert_eq!(pooldata.protocol_fees_y, 0);
+}
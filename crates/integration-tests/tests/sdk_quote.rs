@@ -0,0 +1,113 @@
+// fairswap-sdk's PoolState has to agree with the program to the last unit, including
+// when the high-water-mark clamp kicks in.
+use fairswap::states::{Deadline, PoolData};
+use fairswap_sdk::PoolKeys;
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const DEPOSIT: u64 = 2_000_000;
+
+// Quotes the swap with the SDK, executes it and checks the trader got exactly the quote
+fn swap_and_compare(
+    svm: &mut LiteSVM,
+    keys: &PoolKeys,
+    trader: &Keypair,
+    mint_deposit: Pubkey,
+    amount_in: u64,
+) {
+    let mut state = pool_state(svm, keys);
+    let clock = svm.get_sysvar::<Clock>();
+    let quote = state.swap(mint_deposit, amount_in, 1, &clock).unwrap();
+
+    let ata_out = match mint_deposit == keys.mint_x {
+        true => keys.ata_y(&trader.pubkey()),
+        false => keys.ata_x(&trader.pubkey()),
+    };
+    let before = token_balance(svm, &ata_out);
+    send(
+        svm,
+        &[keys.swap(
+            &trader.pubkey(),
+            mint_deposit,
+            amount_in,
+            quote.amount_out,
+            Deadline::Slot(u64::MAX),
+        )],
+        trader,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(token_balance(svm, &ata_out) - before, quote.amount_out);
+
+    // the simulated state matches the pool's afterwards
+    let onchain = pool_state(svm, keys);
+    assert_eq!(state.vault_x, onchain.vault_x);
+    assert_eq!(state.vault_y, onchain.vault_y);
+    assert_eq!(state.pooldata.last_slot, onchain.pooldata.last_slot);
+    assert_eq!(state.pooldata.buying_x_high, onchain.pooldata.buying_x_high);
+    assert_eq!(state.pooldata.buying_y_high, onchain.pooldata.buying_y_high);
+    assert_eq!(state.pooldata.surplus_x, onchain.pooldata.surplus_x);
+    assert_eq!(state.pooldata.surplus_y, onchain.pooldata.surplus_y);
+    assert_eq!(
+        state.pooldata.protocol_fees_x,
+        onchain.pooldata.protocol_fees_x
+    );
+    assert_eq!(
+        state.pooldata.protocol_fees_y,
+        onchain.pooldata.protocol_fees_y
+    );
+}
+
+#[test]
+fn sdk_quote_matches_swap() {
+    let mut svm = setup();
+    svm.warp_to_slot(100);
+    let admin = new_user(&mut svm);
+    let trader = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_x, &trader.pubkey(), 1_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &trader.pubkey(), 1_000_000);
+
+    let keys = PoolKeys::new(mint_x, mint_y, 1);
+    send(
+        &mut svm,
+        &[
+            keys.initialize(
+                &admin.pubkey(),
+                30,
+                2_000,
+                10,
+                admin.pubkey(),
+                fairswap_sdk::SurplusPolicy::Treasury,
+            ),
+            keys.deposit(
+                &admin.pubkey(),
+                DEPOSIT - PoolData::MINIMUM_LIQUIDITY,
+                DEPOSIT,
+                DEPOSIT,
+                Deadline::Slot(u64::MAX),
+            ),
+        ],
+        &admin,
+        &[],
+    )
+    .unwrap();
+
+    // buy X, sell it back, then buy again into the clamped mark
+    swap_and_compare(&mut svm, &keys, &trader, mint_y, 20_000);
+    swap_and_compare(&mut svm, &keys, &trader, mint_x, 19_000);
+    swap_and_compare(&mut svm, &keys, &trader, mint_y, 20_000);
+    assert!(pool_state(&svm, &keys).pooldata.surplus_x > 0);
+
+    // and once more in a fresh window
+    svm.warp_to_slot(110);
+    swap_and_compare(&mut svm, &keys, &trader, mint_y, 20_000);
+}
@@ -0,0 +1,238 @@
+// StableSwap pools trade pegged pairs on an amplified invariant, so they pay out far more
+// than x * y = k near 1:1. The amplification can only move slowly, through a ramp.
+use fairswap::errors::AmmError;
+use fairswap::states::{Config, CurveType, Deadline, PoolData, SurplusPolicy};
+use fairswap_sdk::math::Curve;
+use fairswap_sdk::PoolKeys;
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    clock::Clock,
+    signature::{Keypair, Signer},
+};
+
+const DEPOSIT: u64 = 2_000_000;
+const FEE: u16 = 30;
+const AMP: u64 = 100;
+const DAY: i64 = 86_400;
+
+struct Pools {
+    admin: Keypair,
+    trader: Keypair,
+    constant_product: PoolKeys,
+    stable: PoolKeys,
+}
+
+// Two pools over the same pair and the same 2e6 / 2e6 deposit, one on each curve
+fn setup_pools() -> (LiteSVM, Pools) {
+    let mut svm = setup();
+    let admin = new_user(&mut svm);
+    let trader = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), 2 * DEPOSIT);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), 2 * DEPOSIT);
+    create_ata(&mut svm, &admin, &mint_x, &trader.pubkey(), DEPOSIT);
+    create_ata(&mut svm, &admin, &mint_y, &trader.pubkey(), 0);
+
+    let pools = Pools {
+        constant_product: PoolKeys::new(mint_x, mint_y, 1),
+        stable: PoolKeys::new(mint_x, mint_y, 2),
+        admin,
+        trader,
+    };
+    let admin_key = pools.admin.pubkey();
+    for (keys, curve, amp) in [
+        (&pools.constant_product, CurveType::ConstantProduct, 0),
+        (&pools.stable, CurveType::StableSwap, AMP),
+    ] {
+        for ix in [
+            keys.initialize_with_curve(
+                &admin_key,
+                FEE,
+                0,
+                10,
+                admin_key,
+                SurplusPolicy::Lp,
+                curve,
+                amp,
+                50,
+                50,
+            ),
+            keys.deposit(
+                &admin_key,
+                DEPOSIT - PoolData::MINIMUM_LIQUIDITY,
+                DEPOSIT,
+                DEPOSIT,
+                Deadline::Slot(u64::MAX),
+            ),
+        ] {
+            send(&mut svm, &[ix], &pools.admin, &[]).unwrap();
+        }
+    }
+    (svm, pools)
+}
+
+// Sells amount_in X into the pool and returns the Y received
+fn sell_x(svm: &mut LiteSVM, keys: &PoolKeys, trader: &Keypair, amount_in: u64) -> u64 {
+    let ata_y = keys.ata_y(&trader.pubkey());
+    let before = token_balance(svm, &ata_y);
+    send(
+        svm,
+        &[keys.swap(
+            &trader.pubkey(),
+            keys.mint_x,
+            amount_in,
+            1,
+            Deadline::Slot(u64::MAX),
+        )],
+        trader,
+        &[],
+    )
+    .unwrap();
+    token_balance(svm, &ata_y) - before
+}
+
+fn set_timestamp(svm: &mut LiteSVM, unix_timestamp: i64) {
+    let clock = svm.get_sysvar::<Clock>();
+    svm.set_sysvar(&Clock {
+        unix_timestamp,
+        ..clock
+    });
+}
+
+#[test]
+fn stable_pool_pays_more_near_the_peg() {
+    let (mut svm, pools) = setup_pools();
+    let amount_in = 100_000;
+
+    // a balanced stable pool mints the same first LP as sqrt(x * y)
+    assert_eq!(
+        token_balance(&svm, &pools.stable.ata_lp(&pools.admin.pubkey())),
+        token_balance(&svm, &pools.constant_product.ata_lp(&pools.admin.pubkey()))
+    );
+
+    let clock = svm.get_sysvar::<Clock>();
+    let quote = pool_state(&svm, &pools.stable)
+        .swap(pools.stable.mint_x, amount_in, 1, &clock)
+        .unwrap();
+    let expected = Curve::StableSwap { amp: AMP }
+        .swap_exact_in(DEPOSIT, DEPOSIT, amount_in, FEE)
+        .unwrap()
+        .withdraw;
+
+    let stable = sell_x(&mut svm, &pools.stable, &pools.trader, amount_in);
+    let constant_product = sell_x(&mut svm, &pools.constant_product, &pools.trader, amount_in);
+    assert_eq!(stable, expected);
+    assert_eq!(stable, quote.amount_out);
+    assert!(stable > constant_product);
+    // within the fee and a little slippage of 1:1
+    assert!(stable > amount_in * 99 / 100);
+}
+
+#[test]
+fn amp_only_moves_through_bounded_ramps() {
+    let (mut svm, pools) = setup_pools();
+    let admin = pools.admin.pubkey();
+    let now = DAY;
+    set_timestamp(&mut svm, now);
+
+    // faster than a day, more than 10x, or on a constant product pool
+    for (keys, target_amp, end_ts, error) in [
+        (&pools.stable, 500, now + DAY - 1, AmmError::InvalidAmpRamp),
+        (
+            &pools.stable,
+            AMP * 10 + 1,
+            now + DAY,
+            AmmError::InvalidAmpRamp,
+        ),
+        (
+            &pools.stable,
+            AMP / 10 - 1,
+            now + DAY,
+            AmmError::InvalidAmpRamp,
+        ),
+        (
+            &pools.constant_product,
+            500,
+            now + DAY,
+            AmmError::UnsupportedCurve,
+        ),
+    ] {
+        let ix = keys.ramp_amp(&admin, target_amp, end_ts);
+        assert_error(send(&mut svm, &[ix], &pools.admin, &[]), error);
+    }
+    let res = send(
+        &mut svm,
+        &[pools
+            .stable
+            .ramp_amp(&pools.trader.pubkey(), 500, now + DAY)],
+        &pools.trader,
+        &[],
+    );
+    assert_error(res, AmmError::Unauthorized);
+
+    send(
+        &mut svm,
+        &[pools.stable.ramp_amp(&admin, 500, now + 2 * DAY)],
+        &pools.admin,
+        &[],
+    )
+    .unwrap();
+    set_timestamp(&mut svm, now + DAY);
+    let config: Config = fetch(&svm, &pools.stable.config);
+    assert_eq!(config.amp.current(now + DAY), 300);
+
+    // stopping holds amp where the ramp has got to
+    send(
+        &mut svm,
+        &[pools.stable.stop_ramp_amp(&admin)],
+        &pools.admin,
+        &[],
+    )
+    .unwrap();
+    let config: Config = fetch(&svm, &pools.stable.config);
+    assert_eq!(config.amp.current(now + 3 * DAY), 300);
+
+    // batches clear on x * y = k only
+    let res = send(
+        &mut svm,
+        &[pools.stable.set_batch_auction(&admin, true)],
+        &pools.admin,
+        &[],
+    );
+    assert_error(res, AmmError::UnsupportedCurve);
+}
+
+#[test]
+fn initialize_checks_the_amp() {
+    let mut svm = setup();
+    let admin = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    let keys = PoolKeys::new(mint_x, mint_y, 1);
+    let admin_key = admin.pubkey();
+
+    for (curve, amp) in [
+        (CurveType::ConstantProduct, AMP),
+        (CurveType::StableSwap, 0),
+        (
+            CurveType::StableSwap,
+            fairswap_sdk::math::stable::MAX_AMP + 1,
+        ),
+    ] {
+        let ix = keys.initialize_with_curve(
+            &admin_key,
+            FEE,
+            0,
+            10,
+            admin_key,
+            SurplusPolicy::Lp,
+            curve,
+            amp,
+            50,
+            50,
+        );
+        assert_error(send(&mut svm, &[ix], &admin, &[]), AmmError::InvalidAmp);
+    }
+}
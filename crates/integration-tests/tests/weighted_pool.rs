@@ -0,0 +1,287 @@
+// Weighted pools hold their value in a fixed split other than 50/50. An 80/20 pool with
+// 4 X for every Y prices them 1:1, and the fairness marks start from that weighted spot.
+use fairswap::errors::AmmError;
+use fairswap::states::{CurveType, Deadline, PoolData, SurplusPolicy};
+use fairswap_sdk::math::{fairness, weighted, Curve};
+use fairswap_sdk::PoolKeys;
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const DEPOSIT_X: u64 = 4_000_000;
+const DEPOSIT_Y: u64 = 1_000_000;
+// sqrt(4e6 * 1e6)
+const LP_SUPPLY: u64 = 2_000_000;
+const FEE: u16 = 30;
+
+struct Pool {
+    keys: PoolKeys,
+    admin: Keypair,
+    trader: Keypair,
+}
+
+// An 80/20 pool of 4e6 X and 1e6 Y, the trader holds 2e6 of each and the admin as much
+// again as the first deposit
+fn setup_weighted_pool() -> (LiteSVM, Pool) {
+    let (mut svm, keys, admin) = setup_pool(PoolParams {
+        fee: FEE,
+        curve: CurveType::Weighted,
+        weights: (80, 20),
+        deposit: (DEPOSIT_X, DEPOSIT_Y),
+        ..Default::default()
+    });
+    mint_to(
+        &mut svm,
+        &admin,
+        &keys.mint_x,
+        &keys.ata_x(&admin.pubkey()),
+        DEPOSIT_X,
+    );
+    mint_to(
+        &mut svm,
+        &admin,
+        &keys.mint_y,
+        &keys.ata_y(&admin.pubkey()),
+        DEPOSIT_Y,
+    );
+    let trader = new_user(&mut svm);
+    create_ata(&mut svm, &admin, &keys.mint_x, &trader.pubkey(), 2_000_000);
+    create_ata(&mut svm, &admin, &keys.mint_y, &trader.pubkey(), 2_000_000);
+    (
+        svm,
+        Pool {
+            keys,
+            admin,
+            trader,
+        },
+    )
+}
+
+fn swap_quoted(svm: &mut LiteSVM, pool: &Pool, mint_deposit: Pubkey, amount_in: u64) -> u64 {
+    let keys = &pool.keys;
+    let trader = pool.trader.pubkey();
+    let clock = svm.get_sysvar::<Clock>();
+    let quote = pool_state(svm, keys)
+        .swap(mint_deposit, amount_in, 1, &clock)
+        .unwrap();
+    let ata_out = match mint_deposit == keys.mint_x {
+        true => keys.ata_y(&trader),
+        false => keys.ata_x(&trader),
+    };
+    let before = token_balance(svm, &ata_out);
+    send(
+        svm,
+        &[keys.swap(
+            &trader,
+            mint_deposit,
+            amount_in,
+            quote.amount_out,
+            Deadline::Slot(u64::MAX),
+        )],
+        &pool.trader,
+        &[],
+    )
+    .unwrap();
+    let received = token_balance(svm, &ata_out) - before;
+    assert_eq!(received, quote.amount_out);
+    received
+}
+
+#[test]
+fn swaps_price_at_the_weighted_spot() {
+    let (mut svm, pool) = setup_weighted_pool();
+
+    // the first window opens at the weighted spot, 1:1 both ways
+    let pooldata: PoolData = fetch(&svm, &pool.keys.pooldata);
+    let spot = fairness::weighted_limit_price(DEPOSIT_Y, 20, DEPOSIT_X, 80, 6).unwrap();
+    assert_eq!(spot, 1_000_000);
+    assert_eq!(pooldata.buying_x_high, Some(spot));
+    assert_eq!(pooldata.buying_y_high, Some(spot));
+
+    let amount_in = 10_000;
+    let expected = Curve::Weighted {
+        weight_in: 80,
+        weight_out: 20,
+    }
+    .swap_exact_in(DEPOSIT_X, DEPOSIT_Y, amount_in, FEE)
+    .unwrap()
+    .withdraw;
+    let received = swap_quoted(&mut svm, &pool, pool.keys.mint_x, amount_in);
+    assert_eq!(received, expected);
+    // close to 1:1 once the fee is paid, where x * y = k would pay about a quarter
+    assert!(received > amount_in * 99 / 100);
+
+    // the other way round trades against the 20% side, still matching the SDK
+    swap_quoted(&mut svm, &pool, pool.keys.mint_y, amount_in);
+}
+
+#[test]
+fn deposits_and_withdrawals_stay_proportional() {
+    let (mut svm, pool) = setup_weighted_pool();
+    let keys = &pool.keys;
+    let admin = pool.admin.pubkey();
+
+    // a tenth of the supply takes a tenth of each reserve, keeping the 4:1 split
+    send(
+        &mut svm,
+        &[keys.deposit(
+            &admin,
+            LP_SUPPLY / 10,
+            DEPOSIT_X,
+            DEPOSIT_Y,
+            Deadline::Slot(u64::MAX),
+        )],
+        &pool.admin,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        token_balance(&svm, &keys.vault_x),
+        DEPOSIT_X + DEPOSIT_X / 10
+    );
+    assert_eq!(
+        token_balance(&svm, &keys.vault_y),
+        DEPOSIT_Y + DEPOSIT_Y / 10
+    );
+
+    send(
+        &mut svm,
+        &[keys.withdraw(&admin, LP_SUPPLY / 10, 1, 1, Deadline::Slot(u64::MAX))],
+        &pool.admin,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(token_balance(&svm, &keys.vault_x), DEPOSIT_X);
+    assert_eq!(token_balance(&svm, &keys.vault_y), DEPOSIT_Y);
+    // the spot, and so the fairness marks, are where the pool started
+    let pooldata: PoolData = fetch(&svm, &keys.pooldata);
+    assert_eq!(pooldata.buying_x_high, Some(1_000_000));
+    assert_eq!(pooldata.buying_y_high, Some(1_000_000));
+}
+
+#[test]
+fn weights_and_trade_size_are_checked() {
+    let (mut svm, pool) = setup_weighted_pool();
+    let admin = pool.admin.pubkey();
+
+    // weights must be a supported split adding up to 100, and only weighted pools may
+    // leave 50/50
+    for (seed, curve, amp, weights) in [
+        (2, CurveType::Weighted, 0, (80, 30)),
+        (3, CurveType::Weighted, 0, (100, 0)),
+        (6, CurveType::Weighted, 0, (63, 37)),
+        (7, CurveType::Weighted, 0, (70, 30)),
+        (8, CurveType::Weighted, 0, (98, 2)),
+        (4, CurveType::ConstantProduct, 0, (80, 20)),
+        (5, CurveType::StableSwap, 100, (80, 20)),
+    ] {
+        let keys = PoolKeys::new(pool.keys.mint_x, pool.keys.mint_y, seed);
+        let ix = keys.initialize_with_curve(
+            &admin,
+            FEE,
+            0,
+            10,
+            admin,
+            SurplusPolicy::Lp,
+            curve,
+            amp,
+            weights.0,
+            weights.1,
+        );
+        assert_error(
+            send(&mut svm, &[ix], &pool.admin, &[]),
+            AmmError::InvalidWeights,
+        );
+    }
+
+    // no single swap may take in more than 30% of a reserve
+    let res = send(
+        &mut svm,
+        &[pool.keys.swap(
+            &pool.trader.pubkey(),
+            pool.keys.mint_x,
+            DEPOSIT_X / 2,
+            1,
+            Deadline::Slot(u64::MAX),
+        )],
+        &pool.trader,
+        &[],
+    );
+    assert_error(res, AmmError::TradeTooLarge);
+}
+
+#[test]
+fn supported_weights_fit_the_compute_budget() {
+    // the default budget of a transaction with a single instruction
+    const COMPUTE_LIMIT: u64 = 200_000;
+    const RESERVE: u64 = 1_000_000_000;
+
+    let mut svm = setup();
+    let admin = new_user(&mut svm);
+    let trader = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    let pools = weighted::SUPPORTED_WEIGHTS.len() as u64;
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), pools * RESERVE);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), pools * RESERVE);
+    create_ata(&mut svm, &admin, &mint_x, &trader.pubkey(), pools * RESERVE);
+    create_ata(&mut svm, &admin, &mint_y, &trader.pubkey(), pools * RESERVE);
+
+    let admin_key = admin.pubkey();
+    let trader_key = trader.pubkey();
+    for (seed, weight_x) in (10..).zip(weighted::SUPPORTED_WEIGHTS) {
+        let keys = PoolKeys::new(mint_x, mint_y, seed);
+        for ix in [
+            keys.initialize_with_curve(
+                &admin_key,
+                FEE,
+                0,
+                10,
+                admin_key,
+                SurplusPolicy::Lp,
+                CurveType::Weighted,
+                0,
+                weight_x,
+                100 - weight_x,
+            ),
+            keys.deposit(&admin_key, 1, RESERVE, RESERVE, Deadline::Slot(u64::MAX)),
+        ] {
+            send(&mut svm, &[ix], &admin, &[]).unwrap();
+        }
+
+        // both directions, each through the forward root and the exact-out search
+        for mint_deposit in [mint_x, mint_y] {
+            for ix in [
+                keys.swap(
+                    &trader_key,
+                    mint_deposit,
+                    RESERVE / 10,
+                    1,
+                    Deadline::Slot(u64::MAX),
+                ),
+                keys.swap_exact_out(
+                    &trader_key,
+                    mint_deposit,
+                    RESERVE / 20,
+                    RESERVE,
+                    Deadline::Slot(u64::MAX),
+                ),
+            ] {
+                // a fresh window each time, so every swap is priced off the curve alone
+                svm.warp_to_slot(current_slot(&svm) + 100);
+                let meta = send(&mut svm, &[ix], &trader, &[]).unwrap();
+                assert!(
+                    meta.compute_units_consumed < COMPUTE_LIMIT,
+                    "{}/{} used {} compute units",
+                    weight_x,
+                    100 - weight_x,
+                    meta.compute_units_consumed
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// Slot warping makes the PoolData.last_slot window reset deterministic, which the
+// TypeScript tests can only approximate against a live validator.
+use fairswap::states::{Deadline, PoolData};
+use integration_tests::*;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const DEPOSIT: u64 = 2_000_000;
+const SWAP: u64 = 20_000;
+const WINDOW_SLOTS: u64 = 10;
+const START_SLOT: u64 = 100;
+
+// Pool of 2e6 X and 2e6 Y opened at START_SLOT, plus a trader holding both tokens
+fn setup_pool() -> (LiteSVM, FairswapPool, Keypair) {
+    let mut svm = setup();
+    svm.warp_to_slot(START_SLOT);
+    let admin = new_user(&mut svm);
+    let trader = new_user(&mut svm);
+    let mint_x = create_mint(&mut svm, &admin, 6);
+    let mint_y = create_mint(&mut svm, &admin, 6);
+    create_ata(&mut svm, &admin, &mint_x, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &admin.pubkey(), 1_000_000_000);
+    create_ata(&mut svm, &admin, &mint_x, &trader.pubkey(), 1_000_000);
+    create_ata(&mut svm, &admin, &mint_y, &trader.pubkey(), 1_000_000);
+
+    let pool = FairswapPool::new(mint_x, mint_y, 1);
+    send(
+        &mut svm,
+        &[pool.initialize_ix(&admin.pubkey(), 0, WINDOW_SLOTS)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    send(
+        &mut svm,
+        &[pool.deposit_ix(
+            &admin.pubkey(),
+            DEPOSIT - PoolData::MINIMUM_LIQUIDITY,
+            DEPOSIT,
+            DEPOSIT,
+        )],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    (svm, pool, trader)
+}
+
+fn swap(svm: &mut LiteSVM, pool: &FairswapPool, trader: &Keypair, mint_in: Pubkey) {
+    send(
+        svm,
+        &[pool.swap_ix(&trader.pubkey(), mint_in, SWAP, 1, Deadline::Slot(u64::MAX))],
+        trader,
+        &[],
+    )
+    .unwrap();
+}
+
+// Buys X to set the buying_x_high mark, then sells X back so the spot price reverts
+fn buy_and_revert(svm: &mut LiteSVM, pool: &FairswapPool, trader: &Keypair) -> u128 {
+    swap(svm, pool, trader, pool.mint_y);
+    let high = pool.pooldata(svm).buying_x_high.unwrap();
+    swap(svm, pool, trader, pool.mint_x);
+    high
+}
+
+#[test]
+fn high_water_mark_holds_within_the_window() {
+    let (mut svm, pool, trader) = setup_pool();
+    let high = buy_and_revert(&mut svm, &pool, &trader);
+    assert_eq!(pool.pooldata(&svm).surplus_x, 0);
+
+    // last slot of the window
+    svm.warp_to_slot(START_SLOT + WINDOW_SLOTS - 1);
+    swap(&mut svm, &pool, &trader, pool.mint_y);
+
+    let pooldata = pool.pooldata(&svm);
+    assert_eq!(pooldata.last_slot, START_SLOT);
+    assert_eq!(pooldata.buying_x_high, Some(high));
+    // the cheaper post-reversion price was clamped to the mark
+    assert!(pooldata.surplus_x > 0);
+}
+
+#[test]
+fn window_resets_once_window_slots_have_passed() {
+    let (mut svm, pool, trader) = setup_pool();
+    let high = buy_and_revert(&mut svm, &pool, &trader);
+
+    svm.warp_to_slot(START_SLOT + WINDOW_SLOTS);
+    assert_eq!(current_slot(&svm), START_SLOT + WINDOW_SLOTS);
+    swap(&mut svm, &pool, &trader, pool.mint_y);
+
+    let pooldata = pool.pooldata(&svm);
+    assert_eq!(pooldata.last_slot, START_SLOT + WINDOW_SLOTS);
+    assert!(pooldata.buying_x_high.unwrap() < high);
+    assert_eq!(pooldata.surplus_x, 0);
+}
+
+#[test]
+fn window_opened_by_a_late_swap_lasts_window_slots() {
+    let (mut svm, pool, trader) = setup_pool();
+
+    // nothing trades for a while, the first swap after that opens a new window
+    let reset_slot = START_SLOT + 3 * WINDOW_SLOTS + 5;
+    svm.warp_to_slot(reset_slot);
+    let high = buy_and_revert(&mut svm, &pool, &trader);
+    assert_eq!(pool.pooldata(&svm).last_slot, reset_slot);
+
+    svm.warp_to_slot(reset_slot + WINDOW_SLOTS - 1);
+    swap(&mut svm, &pool, &trader, pool.mint_y);
+    let pooldata = pool.pooldata(&svm);
+    assert_eq!(pooldata.last_slot, reset_slot);
+    assert_eq!(pooldata.buying_x_high, Some(high));
+}
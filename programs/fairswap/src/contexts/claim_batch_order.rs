@@ -0,0 +1,240 @@
+use crate::errors::AmmError;
+use crate::events::BatchOrderClaimed;
+use crate::states::{Batch, BatchOrder, Config, PoolData};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use fairswap_math::batch;
+
+#[derive(Accounts)]
+pub struct ClaimBatchOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: only receives the batch's rent back, checked against the batch
+    #[account(mut, address = batch.payer)]
+    pub batch_payer: UncheckedAccount<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: this is safe
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump = config.bump_auth,
+    )]
+    pub auth: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x,
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y,
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"batch", config.key().as_ref(), batch.slot.to_le_bytes().as_ref()],
+        bump = batch.bump,
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+
+    #[account(
+        mut,
+        close = user,
+        has_one = batch,
+        seeds = [b"order", batch.key().as_ref(), user.key().as_ref()],
+        bump = order.bump,
+    )]
+    pub order: Box<Account<'info, BatchOrder>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimBatchOrder<'info> {
+    // Pays out the order's share of the cleared batch and closes it. A side whose share
+    // falls short of its minimum gets its input back instead, the swap fee stays with the
+    // pool. The last claim also returns rounding dust to the reserves and closes the batch.
+    pub fn claim_batch_order(&mut self) -> Result<()> {
+        require!(self.batch.cleared, AmmError::BatchNotCleared);
+
+        // X goes to the side that deposited Y and the other way round
+        let amount_x = share(
+            self.order.amount_in_y,
+            self.batch.amount_in_y,
+            self.batch.amount_out_x,
+        )?;
+        let amount_y = share(
+            self.order.amount_in_x,
+            self.batch.amount_in_x,
+            self.batch.amount_out_y,
+        )?;
+        // a missed side's share is released like any other, back into the reserves the
+        // refunded input is taken from
+        let (paid_x, refund_y) = match amount_x < self.order.min_out_x {
+            true => (0, self.order.amount_in_y),
+            false => (amount_x, 0),
+        };
+        let (paid_y, refund_x) = match amount_y < self.order.min_out_y {
+            true => (0, self.order.amount_in_x),
+            false => (amount_y, 0),
+        };
+        let payout_x = paid_x.checked_add(refund_x).ok_or(AmmError::Overflow)?;
+        let payout_y = paid_y.checked_add(refund_y).ok_or(AmmError::Overflow)?;
+
+        // the last claim also releases the rounding dust of the pro-rata shares
+        self.batch.open_orders = self
+            .batch
+            .open_orders
+            .checked_sub(1)
+            .ok_or(AmmError::Underflow)?;
+        let (release_x, release_y) = match self.batch.open_orders {
+            0 => (
+                self.batch
+                    .amount_out_x
+                    .checked_sub(self.batch.claimed_x)
+                    .ok_or(AmmError::Underflow)?,
+                self.batch
+                    .amount_out_y
+                    .checked_sub(self.batch.claimed_y)
+                    .ok_or(AmmError::Underflow)?,
+            ),
+            _ => (amount_x, amount_y),
+        };
+        self.pooldata.batch_x = self
+            .pooldata
+            .batch_x
+            .checked_sub(release_x)
+            .ok_or(AmmError::Underflow)?;
+        self.pooldata.batch_y = self
+            .pooldata
+            .batch_y
+            .checked_sub(release_y)
+            .ok_or(AmmError::Underflow)?;
+        self.batch.claimed_x = self
+            .batch
+            .claimed_x
+            .checked_add(amount_x)
+            .ok_or(AmmError::Overflow)?;
+        self.batch.claimed_y = self
+            .batch
+            .claimed_y
+            .checked_add(amount_y)
+            .ok_or(AmmError::Overflow)?;
+
+        if payout_x > 0 {
+            self.withdraw_token(true, payout_x)?;
+        }
+        if payout_y > 0 {
+            self.withdraw_token(false, payout_y)?;
+        }
+
+        emit!(BatchOrderClaimed {
+            config: self.config.key(),
+            batch: self.batch.key(),
+            user: self.user.key(),
+            amount_x: payout_x,
+            amount_y: payout_y,
+            refund_x,
+            refund_y,
+        });
+
+        if self.batch.open_orders == 0 {
+            self.batch.close(self.batch_payer.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
+        let signer_seeds = &[&seeds[..]];
+
+        let (mint, decimals, vault, ata, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.vault_x.to_account_info(),
+                self.user_ata_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.vault_y.to_account_info(),
+                self.user_ata_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from: vault,
+            to: ata,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+        transfer_checked(ctx, amount, decimals)
+    }
+}
+
+// an order with nothing on a side has no share of that side's payout
+fn share(amount_in: u64, total_in: u64, total_out: u64) -> Result<u64> {
+    match amount_in {
+        0 => Ok(0),
+        _ => Ok(batch::pro_rata(amount_in, total_in, total_out).map_err(AmmError::from)?),
+    }
+}
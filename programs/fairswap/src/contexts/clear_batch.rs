@@ -0,0 +1,135 @@
+use crate::errors::AmmError;
+use crate::events::BatchCleared;
+use crate::{
+    assert_not_locked,
+    helpers::calculate_limit_price,
+    states::{Batch, Config, PoolData},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use fairswap_math::batch;
+
+// Permissionless crank, anyone can clear a batch once its slot has passed
+#[derive(Accounts)]
+pub struct ClearBatch<'info> {
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"batch", config.key().as_ref(), batch.slot.to_le_bytes().as_ref()],
+        bump = batch.bump,
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClearBatch<'info> {
+    // Settles every order in the batch at one price. Nothing is transferred here, the
+    // payouts stay in the vaults until each order is claimed.
+    pub fn clear_batch(&mut self) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.batch.cleared, AmmError::BatchCleared);
+        let clock = Clock::get()?;
+        require!(self.batch.is_closed(clock.slot), AmmError::BatchOpen);
+
+        let reserves = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        self.pooldata
+            .update_oracle(reserves.0, reserves.1, curve, clock.unix_timestamp)?;
+
+        // the swap fees join the reserves first, so both sides trade against them
+        let (fee_x, fee_y) = (self.batch.fee_x, self.batch.fee_y);
+        self.pooldata.batch_x = self
+            .pooldata
+            .batch_x
+            .checked_sub(fee_x)
+            .ok_or(AmmError::Underflow)?;
+        self.pooldata.batch_y = self
+            .pooldata
+            .batch_y
+            .checked_sub(fee_y)
+            .ok_or(AmmError::Underflow)?;
+        self.pooldata
+            .accrue_protocol_fee(true, fee_x, self.config.protocol_fee_bps)?;
+        self.pooldata
+            .accrue_protocol_fee(false, fee_y, self.config.protocol_fee_bps)?;
+
+        let (reserve_x, reserve_y) = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let (in_x, in_y) = (self.batch.amount_in_x, self.batch.amount_in_y);
+        let clearing = batch::clear(reserve_x, reserve_y, in_x, in_y).map_err(AmmError::from)?;
+
+        // the inputs become reserves and the payouts are held back for the claims
+        self.pooldata.batch_x = self
+            .pooldata
+            .batch_x
+            .checked_sub(in_x)
+            .and_then(|x| x.checked_add(clearing.out_x))
+            .ok_or(AmmError::Underflow)?;
+        self.pooldata.batch_y = self
+            .pooldata
+            .batch_y
+            .checked_sub(in_y)
+            .and_then(|y| y.checked_add(clearing.out_y))
+            .ok_or(AmmError::Underflow)?;
+
+        self.batch.amount_out_x = clearing.out_x;
+        self.batch.amount_out_y = clearing.out_y;
+        self.batch.cleared = true;
+
+        // reserve_x + in_x and reserve_y + in_y both come out of one vault, so fit a u64
+        let ratio =
+            calculate_limit_price(reserve_y + in_y, reserve_x + in_x, self.pooldata.precision)?;
+        msg!("clearing ratio: {}", ratio);
+
+        emit!(BatchCleared {
+            config: self.config.key(),
+            batch: self.batch.key(),
+            slot: self.batch.slot,
+            amount_in_x: in_x,
+            amount_in_y: in_y,
+            amount_out_x: clearing.out_x,
+            amount_out_y: clearing.out_y,
+            ratio,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use crate::errors::AmmError;
+use crate::events::SurplusCollected;
+use crate::states::{Config, PoolData};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct CollectSurplus<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: checked against the pool's surplus policy
+    #[account(
+        constraint = config.surplus_recipient() == Some(recipient.key()) @ AmmError::InvalidSurplusRecipient
+    )]
+    pub recipient: UncheckedAccount<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: this is safe
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump = config.bump_auth,
+    )]
+    pub auth: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_x,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program_x,
+    )]
+    pub recipient_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_y,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program_y,
+    )]
+    pub recipient_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CollectSurplus<'info> {
+    pub fn collect_surplus(&mut self) -> Result<()> {
+        let (amount_x, amount_y) = (
+            self.pooldata.pending_surplus_x,
+            self.pooldata.pending_surplus_y,
+        );
+
+        if amount_x > 0 {
+            self.pooldata.pending_surplus_x = 0;
+            self.transfer_surplus(true, amount_x)?;
+        }
+        if amount_y > 0 {
+            self.pooldata.pending_surplus_y = 0;
+            self.transfer_surplus(false, amount_y)?;
+        }
+
+        emit!(SurplusCollected {
+            config: self.config.key(),
+            recipient: self.recipient.key(),
+            amount_x,
+            amount_y,
+        });
+        Ok(())
+    }
+
+    pub fn transfer_surplus(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
+        let signer_seeds = &[&seeds[..]];
+
+        let (mint, decimals, vault, ata, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.vault_x.to_account_info(),
+                self.recipient_ata_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.vault_y.to_account_info(),
+                self.recipient_ata_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from: vault,
+            to: ata,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+
+        transfer_checked(ctx, amount, decimals)
+    }
+}
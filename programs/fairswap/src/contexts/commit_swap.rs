@@ -0,0 +1,172 @@
+use crate::errors::AmmError;
+use crate::events::SwapCommitted;
+use crate::{
+    assert_not_locked,
+    helpers::get_transfer_fee,
+    states::{Config, PoolData, SwapCommitment},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(hash: [u8; 32])]
+pub struct CommitSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x,
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y,
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + SwapCommitment::INIT_SPACE,
+        seeds = [b"commitment", config.key().as_ref(), user.key().as_ref(), hash.as_ref()],
+        bump,
+    )]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitSwap<'info> {
+    // Escrows amount_x and amount_y behind the hash of a swap to be revealed later. Only
+    // the revealed input is swapped, the rest of the escrow goes back on reveal.
+    pub fn commit_swap(
+        &mut self,
+        hash: [u8; 32],
+        amount_x: u64,
+        amount_y: u64,
+        bumps: &CommitSwapBumps,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+        require!(amount_x > 0 || amount_y > 0, AmmError::ZeroBalance);
+
+        let escrow_x = amount_x - get_transfer_fee(&self.mint_x.to_account_info(), amount_x)?;
+        let escrow_y = amount_y - get_transfer_fee(&self.mint_y.to_account_info(), amount_y)?;
+        self.pooldata.committed_x = self
+            .pooldata
+            .committed_x
+            .checked_add(escrow_x)
+            .ok_or(AmmError::Overflow)?;
+        self.pooldata.committed_y = self
+            .pooldata
+            .committed_y
+            .checked_add(escrow_y)
+            .ok_or(AmmError::Overflow)?;
+
+        self.commitment.set_inner(SwapCommitment {
+            config: self.config.key(),
+            owner: self.user.key(),
+            hash,
+            amount_x: escrow_x,
+            amount_y: escrow_y,
+            slot: Clock::get()?.slot,
+            bump: bumps.commitment,
+        });
+
+        if amount_x > 0 {
+            self.deposit_token(true, amount_x)?;
+        }
+        if amount_y > 0 {
+            self.deposit_token(false, amount_y)?;
+        }
+
+        emit!(SwapCommitted {
+            config: self.config.key(),
+            commitment: self.commitment.key(),
+            user: self.user.key(),
+            hash,
+            amount_x: escrow_x,
+            amount_y: escrow_y,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (mint, decimals, from, to, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.user_ata_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.user_ata_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from,
+            mint,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(token_program, accounts);
+        transfer_checked(ctx, amount, decimals)
+    }
+}
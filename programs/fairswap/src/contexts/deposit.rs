@@ -1,15 +1,20 @@
 use crate::errors::AmmError;
-// use crate::states::PoolData;
-use crate::{assert_not_locked, states::Config};
+use crate::events::LiquidityAdded;
+use crate::{
+    assert_not_expired, assert_not_locked,
+    helpers::{get_transfer_fee, get_transfer_inverse_fee, lp_decimals},
+    states::{Config, Deadline, PoolData},
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{mint_to, transfer_checked, MintTo, TransferChecked},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{
+        mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
 
 use crate::assert_non_zero;
-use constant_product_curve::ConstantProduct;
+use fairswap_math::curve;
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -21,7 +26,7 @@ pub struct Deposit<'info> {
 
     /// CHECK: this is safe
     #[account(
-        seeds = [b"auth"],
+        seeds = [b"auth", config.key().as_ref()],
         bump = config.bump_auth,
     )]
     pub auth: UncheckedAccount<'info>,
@@ -30,7 +35,7 @@ pub struct Deposit<'info> {
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = user,
-        // associated_token::token_program = token_program,
+        associated_token::token_program = token_program_x,
     )]
     pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -38,7 +43,7 @@ pub struct Deposit<'info> {
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = user,
-        // associated_token::token_program = token_program,
+        associated_token::token_program = token_program_y,
     )]
     pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -47,8 +52,9 @@ pub struct Deposit<'info> {
         seeds = [b"mint_lp", config.key().as_ref()],
         bump,
         payer = user,
-        mint::decimals = 6,
-        mint::authority = auth
+        mint::decimals = lp_decimals(mint_x.decimals, mint_y.decimals),
+        mint::authority = auth,
+        mint::token_program = token_program_lp,
     )]
     pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
 
@@ -57,23 +63,39 @@ pub struct Deposit<'info> {
         payer = user,
         associated_token::mint = mint_lp,
         associated_token::authority = user,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_lp,
     )]
     pub user_ata_lp: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    // holds the MINIMUM_LIQUIDITY minted on the first deposit, no instruction moves it out
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"locked_lp", config.key().as_ref()],
+        bump,
+        token::mint = mint_lp,
+        token::authority = auth,
+        token::token_program = token_program_lp,
+    )]
+    pub locked_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(
         mut,
-        associated_token::mint = mint_x,
-        associated_token::authority = auth,
-        associated_token::token_program = token_program,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
     )]
     pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
-        associated_token::mint = mint_y,
-        associated_token::authority = auth,
-        associated_token::token_program = token_program,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
     )]
     pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -83,83 +105,136 @@ pub struct Deposit<'info> {
     )]
     pub config: Box<Account<'info, Config>>,
 
-    // #[account(
-    //     mut,
-    //     seeds = [b"pooldata", config.key().as_ref()],
-    //     bump = pooldata.bump,
-    // )]
-    // pooldata: Account<'info, PoolData>,
-    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub token_program_lp: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Deposit<'info> {
-    pub fn deposit(&mut self, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
+    pub fn deposit(
+        &mut self,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
         msg!("Starting deposit");
         require!(!self.config.locked, AmmError::PoolLocked);
         assert_non_zero!([amount, max_x, max_y]);
         assert_not_locked!(self.config.locked);
-        // let mut is_initialization = false;
-        let (x, y) = match self.mint_lp.supply == 0
-            && self.vault_x.amount == 0
-            && self.vault_y.amount == 0
-        {
+        assert_not_expired!(deadline);
+        let (reserve_x, reserve_y) = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let clock = Clock::get()?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        self.pooldata
+            .update_oracle(reserve_x, reserve_y, curve, clock.unix_timestamp)?;
+        let mint_x = self.mint_x.to_account_info();
+        let mint_y = self.mint_y.to_account_info();
+        // anyone can send tokens to the vaults, so only the LP supply says whether the pool
+        // has been funded yet
+        let is_initialization = self.mint_lp.supply == 0;
+        // (amount_x, amount_y) is what the user sends, lp_amount what they receive
+        let (amount_x, amount_y, lp_amount) = match is_initialization {
             true => {
-                // is_initialization = true;
-                (max_x, max_y)
+                // the first deposit prices the pool, its LP supply is fixed by what lands
+                // in the vaults and amount only bounds it from below. Anything donated
+                // before it goes to the first LP.
+                let x = (max_x - get_transfer_fee(&mint_x, max_x)?)
+                    .checked_add(reserve_x)
+                    .ok_or(AmmError::Overflow)?;
+                let y = (max_y - get_transfer_fee(&mint_y, max_y)?)
+                    .checked_add(reserve_y)
+                    .ok_or(AmmError::Overflow)?;
+                let liquidity = curve.initial_liquidity(x, y).map_err(AmmError::from)?;
+                require!(
+                    liquidity > PoolData::MINIMUM_LIQUIDITY,
+                    AmmError::InsufficientLiquidity
+                );
+                let lp_amount = liquidity - PoolData::MINIMUM_LIQUIDITY;
+                require!(lp_amount >= amount, AmmError::SlippageExceeded);
+                self.pooldata.seed_window(x, y, curve, clock.slot)?;
+                (max_x, max_y, lp_amount)
             }
             false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount,
-                    self.vault_y.amount,
+                // (x, y) is what has to land in the vaults, after any transfer fee
+                let (x, y) =
+                    curve::deposit_amounts(reserve_x, reserve_y, self.mint_lp.supply, amount)
+                        .map_err(AmmError::from)?;
+                self.pooldata.rescale_window(
+                    (reserve_x, reserve_y),
+                    curve,
                     self.mint_lp.supply,
+                    self.mint_lp
+                        .supply
+                        .checked_add(amount)
+                        .ok_or(AmmError::Overflow)?,
+                    clock.slot,
+                )?;
+                (
+                    x.checked_add(get_transfer_inverse_fee(&mint_x, x)?)
+                        .ok_or(AmmError::Overflow)?,
+                    y.checked_add(get_transfer_inverse_fee(&mint_y, y)?)
+                        .ok_or(AmmError::Overflow)?,
                     amount,
-                    6,
                 )
-                .map_err(AmmError::from)?;
-                (amounts.x, amounts.y)
             }
         };
 
-        // if is_initialization {
-        //     self.pooldata.last_slot = Clock::get()?.slot;
-        //     self.pooldata.buying_x_high = calculate_limit_price(x, y, self.pooldata.precision)?;
-        //     self.pooldata.buying_y_high = calculate_limit_price(y, x, self.pooldata.precision)?;
-        //     // self.pooldata.price_x_low = (x as u128)
-        //     //     .checked_mul(10u128.checked_pow(self.pooldata.precision as u32).ok_or(AmmError::InvalidPrecision)? as u128)
-        //     //     .ok_or(AmmError::Overflow)?
-        //     //     .checked_div(y as u128)
-        //     //     .ok_or(AmmError::Overflow)?;
-        //     // self.pooldata.price_y_low = (y as u128)
-        //     //     .checked_mul(10u128.checked_pow(self.pooldata.precision as u32).ok_or(AmmError::InvalidPrecision)? as u128)
-        //     //     .ok_or(AmmError::Overflow)?
-        //     //     .checked_div(x as u128)
-        //     //     .ok_or(AmmError::Overflow)?;
-        //     // x vault:100, y: 10 => x fiyatı: x/y = 100/10 = 10. bu en kötü fiyat. yani x alırken 10dan fazla alamayacak
-        // }
-
         msg!("Checking slippage");
-        require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+        require!(
+            amount_x <= max_x && amount_y <= max_y,
+            AmmError::SlippageExceeded
+        );
 
         msg!("Depositing token x");
-        self.deposit_tokens(true, x)?;
+        self.deposit_tokens(true, amount_x)?;
 
         msg!("Depositing token y");
-        self.deposit_tokens(false, y)?;
+        self.deposit_tokens(false, amount_y)?;
+
+        if is_initialization {
+            msg!("Locking minimum liquidity");
+            self.mint_lp_token(
+                self.locked_lp.to_account_info(),
+                PoolData::MINIMUM_LIQUIDITY,
+            )?;
+        }
 
         msg!("Minting LP tokens");
-        self.mint_lp_token(amount)
+        self.mint_lp_token(self.user_ata_lp.to_account_info(), lp_amount)?;
+
+        self.mint_lp.reload()?;
+        emit!(LiquidityAdded {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount,
+            lp_supply: self.mint_lp.supply,
+        });
+        Ok(())
     }
 
     pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
         msg!("deposit tokens func started");
 
         msg!("Checking if token is x or y");
-        let mint;
+        let (mint, token_program);
         let (from, to) = match is_x {
             true => {
                 mint = self.mint_x.clone();
+                token_program = self.token_program_x.to_account_info();
                 (
                     self.user_ata_x.to_account_info(),
                     self.vault_x.to_account_info(),
@@ -167,6 +242,7 @@ impl<'info> Deposit<'info> {
             }
             false => {
                 mint = self.mint_y.clone();
+                token_program = self.token_program_y.to_account_info();
                 (
                     self.user_ata_y.to_account_info(),
                     self.vault_y.to_account_info(),
@@ -182,18 +258,18 @@ impl<'info> Deposit<'info> {
             authority: self.user.to_account_info(),
         };
 
-        let ctx = CpiContext::new(self.token_program.to_account_info(), accounts);
+        let ctx = CpiContext::new(token_program, accounts);
         msg!("Transferring tokens");
-        transfer_checked(ctx, amount, 6)?;
+        transfer_checked(ctx, amount, mint.decimals)?;
 
         Ok(())
     }
 
-    pub fn mint_lp_token(&mut self, amount: u64) -> Result<()> {
+    pub fn mint_lp_token(&mut self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         let accounts = MintTo {
             mint: self.mint_lp.to_account_info(),
             authority: self.auth.to_account_info(),
-            to: self.user_ata_lp.to_account_info(),
+            to,
         };
 
         // let binding_mint_x = self.mint_x.to_account_info().key();
@@ -206,11 +282,12 @@ impl<'info> Deposit<'info> {
         //     &binding_seed.as_ref(),
         //     &[self.config.bump],
         // ];
-        let seeds = &[&b"auth"[..], &[self.config.bump_auth]];
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
         let signer_seeds = &[&seeds[..]];
 
         let ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
+            self.token_program_lp.to_account_info(),
             accounts,
             signer_seeds,
         );
@@ -0,0 +1,214 @@
+use crate::assert_non_zero;
+use crate::errors::AmmError;
+use crate::events::{LimitOrderFilled, Swapped};
+use crate::{
+    assert_not_locked,
+    helpers::{calculate_limit_price, get_transfer_fee},
+    states::{Config, LimitOrder, PoolData},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+// Permissionless, any keeper can fill an order once the pool pays its limit
+#[derive(Accounts)]
+pub struct FillLimitOrder<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    /// CHECK: receives the output and the order's rent, checked against the order
+    #[account(mut, address = order.owner)]
+    pub owner: UncheckedAccount<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: this is safe
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump = config.bump_auth,
+    )]
+    pub auth: UncheckedAccount<'info>,
+
+    // created when the order was placed
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x,
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y,
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        mut,
+        close = owner,
+        has_one = config,
+        seeds = [b"limit_order", config.key().as_ref(), owner.key().as_ref(), order.id.to_le_bytes().as_ref()],
+        bump = order.bump,
+    )]
+    pub order: Box<Account<'info, LimitOrder>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FillLimitOrder<'info> {
+    // Sells the whole escrow through the same curve and fairness clamp as a swap, failing
+    // unless the owner ends up with at least min_ratio output per unit of input.
+    pub fn fill_limit_order(&mut self) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+
+        let is_buying_x = self.order.mint_in == self.mint_y.key();
+        let amount_in = self.order.amount_in;
+
+        // priced before the escrow is released, so the input isn't counted as reserves yet
+        let clock = Clock::get()?;
+        let reserves = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        self.pooldata
+            .update_oracle(reserves.0, reserves.1, curve, clock.unix_timestamp)?;
+        let res = self.pooldata.fair_swap(
+            reserves,
+            curve,
+            self.config.fee,
+            is_buying_x,
+            amount_in,
+            0,
+            clock.slot,
+        )?;
+        self.pooldata
+            .accrue_protocol_fee(!is_buying_x, res.fee, self.config.protocol_fee_bps)?;
+
+        let surplus = res.raw_withdraw - res.withdraw;
+        self.pooldata
+            .capture_surplus(is_buying_x, surplus, self.config.surplus_policy)?;
+        msg!("surplus: {}", surplus);
+
+        let mint_out = match is_buying_x {
+            true => self.mint_x.to_account_info(),
+            false => self.mint_y.to_account_info(),
+        };
+        let amount_received = res.withdraw - get_transfer_fee(&mint_out, res.withdraw)?;
+        assert_non_zero!([res.deposit, amount_received]);
+        let ratio = calculate_limit_price(amount_received, amount_in, self.pooldata.precision)?;
+        msg!("fill ratio: {}", ratio);
+        require!(ratio >= self.order.min_ratio, AmmError::LimitNotReached);
+
+        let escrow = match is_buying_x {
+            true => &mut self.pooldata.limit_orders_y,
+            false => &mut self.pooldata.limit_orders_x,
+        };
+        *escrow = escrow.checked_sub(amount_in).ok_or(AmmError::Underflow)?;
+
+        self.withdraw_token(is_buying_x, res.withdraw)?;
+
+        // the reward leaves the order before it closes, the owner gets back only the rent
+        let keeper_reward = self.order.keeper_reward;
+        self.order.sub_lamports(keeper_reward)?;
+        self.keeper.add_lamports(keeper_reward)?;
+
+        emit!(Swapped {
+            config: self.config.key(),
+            user: self.owner.key(),
+            mint_in: self.order.mint_in,
+            amount_in,
+            amount_out_raw: res.raw_withdraw,
+            amount_out: res.withdraw,
+            ratio: res.ratio,
+            buying_x_high: self.pooldata.buying_x_high,
+            buying_y_high: self.pooldata.buying_y_high,
+            window_start_slot: self.pooldata.last_slot,
+        });
+        emit!(LimitOrderFilled {
+            config: self.config.key(),
+            order: self.order.key(),
+            owner: self.owner.key(),
+            keeper: self.keeper.key(),
+            amount_in,
+            amount_out: res.withdraw,
+            ratio,
+            keeper_reward,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
+        let signer_seeds = &[&seeds[..]];
+
+        let (mint, decimals, vault, ata, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.vault_x.to_account_info(),
+                self.owner_ata_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.vault_y.to_account_info(),
+                self.owner_ata_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from: vault,
+            to: ata,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+        transfer_checked(ctx, amount, decimals)
+    }
+}
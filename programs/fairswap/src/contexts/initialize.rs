@@ -1,12 +1,10 @@
 use anchor_lang::prelude::*;
 // use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token_interface::{Mint, TokenAccount, TokenInterface},
-};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::errors::AmmError;
-use crate::states::{Config, PoolData};
+use crate::events::PoolInitialized;
+use crate::states::{AmpRamp, Config, CurveType, Observation, PoolData, SurplusPolicy};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -20,24 +18,24 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = admin,
-        associated_token::mint = mint_x,
-        associated_token::authority = auth,
-        // associated_token::token_program = token_program,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
     )]
-    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub config: Box<Account<'info, Config>>,
 
     #[account(
         init,
         payer = admin,
-        associated_token::mint = mint_y,
-        associated_token::authority = auth,
-        // associated_token::token_program = token_program,
+        space = 8 + PoolData::INIT_SPACE,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump
     )]
-    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub pooldata: Box<Account<'info, PoolData>>,
 
     /// CHECK: this is safe
     #[account(
-        seeds = [b"auth"],
+        seeds = [b"auth", config.key().as_ref()],
         bump,
     )]
     pub auth: UncheckedAccount<'info>,
@@ -45,20 +43,24 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + Config::INIT_SPACE,
-        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), seed.to_le_bytes().as_ref()],
-        bump
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
     )]
-    pub config: Box<Account<'info, Config>>,
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
         payer = admin,
-        space = 8 + PoolData::INIT_SPACE,
-        seeds = [b"pooldata", config.key().as_ref()],
-        bump
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
     )]
-    pub pooldata: Box<Account<'info, PoolData>>,
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
 
     // #[account(
     //     init,
@@ -69,32 +71,100 @@ pub struct Initialize<'info> {
     //     mint::decimals = 6,
     // )]
     // pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
-    pub token_program: Interface<'info, TokenInterface>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Initialize<'info> {
-    pub fn save_config(&mut self, seed: u64, fee: u16, bumps: &InitializeBumps) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_config(
+        &mut self,
+        seed: u64,
+        fee: u16,
+        protocol_fee_bps: u16,
+        window_slots: u64,
+        treasury: Pubkey,
+        surplus_policy: SurplusPolicy,
+        curve: CurveType,
+        amp: u64,
+        weight_x: u8,
+        weight_y: u8,
+        bumps: &InitializeBumps,
+    ) -> Result<()> {
         require!(fee < 10000, AmmError::InvalidFeeSet);
+        require!(protocol_fee_bps <= 10000, AmmError::InvalidFee);
+        require!(
+            PoolData::is_valid_window(window_slots),
+            AmmError::InvalidWindowSlots
+        );
+        require!(Config::is_valid_amp(curve, amp), AmmError::InvalidAmp);
+        require!(
+            Config::is_valid_weights(curve, weight_x, weight_y),
+            AmmError::InvalidWeights
+        );
+        let precision = PoolData::precision_for(self.mint_x.decimals, self.mint_y.decimals)?;
         self.config.set_inner(Config {
-            authority: self.admin.key(),
+            authority: Some(self.admin.key()),
+            pending_authority: None,
             seed,
             fee,
+            protocol_fee_bps,
             locked: false,
             mint_x: self.mint_x.key(),
             mint_y: self.mint_y.key(),
+            treasury,
+            surplus_policy,
+            batch_auction: false,
+            curve,
+            amp: AmpRamp::fixed(amp),
+            weight_x,
+            weight_y,
             // bump_lp: bumps.mint_lp,
             bump: bumps.config,
             bump_auth: bumps.auth,
         });
         self.pooldata.set_inner(PoolData {
             last_slot: 0,
+            window_slots,
             buying_x_high: None,
             buying_y_high: None,
-            precision: 6,
+            surplus_x: 0,
+            surplus_y: 0,
+            pending_surplus_x: 0,
+            pending_surplus_y: 0,
+            protocol_fees_x: 0,
+            protocol_fees_y: 0,
+            batch_x: 0,
+            batch_y: 0,
+            committed_x: 0,
+            committed_y: 0,
+            limit_orders_x: 0,
+            limit_orders_y: 0,
+            price_x_cumulative: 0,
+            price_y_cumulative: 0,
+            oracle_timestamp: 0,
+            observations: [Observation::default(); PoolData::OBSERVATIONS],
+            observation_index: 0,
+            precision,
             bump: bumps.pooldata,
         });
+
+        emit!(PoolInitialized {
+            config: self.config.key(),
+            authority: self.admin.key(),
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee,
+            protocol_fee_bps,
+            window_slots,
+            treasury,
+            surplus_policy,
+            curve,
+            amp,
+            weight_x,
+            weight_y,
+        });
         Ok(())
     }
 }
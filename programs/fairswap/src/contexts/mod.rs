@@ -12,3 +12,42 @@ pub use swap::*;
 
 pub mod update;
 pub use update::*;
+
+pub mod collect_surplus;
+pub use collect_surplus::*;
+
+pub mod collect_protocol_fees;
+pub use collect_protocol_fees::*;
+
+pub mod quote;
+pub use quote::*;
+
+pub mod route_swap;
+pub use route_swap::*;
+
+pub mod place_batch_order;
+pub use place_batch_order::*;
+
+pub mod clear_batch;
+pub use clear_batch::*;
+
+pub mod claim_batch_order;
+pub use claim_batch_order::*;
+
+pub mod commit_swap;
+pub use commit_swap::*;
+
+pub mod reveal_swap;
+pub use reveal_swap::*;
+
+pub mod refund_commitment;
+pub use refund_commitment::*;
+
+pub mod place_limit_order;
+pub use place_limit_order::*;
+
+pub mod fill_limit_order;
+pub use fill_limit_order::*;
+
+pub mod cancel_limit_order;
+pub use cancel_limit_order::*;
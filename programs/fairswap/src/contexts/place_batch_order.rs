@@ -0,0 +1,248 @@
+use crate::assert_non_zero;
+use crate::errors::AmmError;
+use crate::events::BatchOrderPlaced;
+use crate::{
+    assert_not_locked,
+    helpers::get_transfer_fee,
+    states::{Batch, BatchOrder, Config, PoolData},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use fairswap_math::curve;
+
+#[derive(Accounts)]
+#[instruction(slot: u64)]
+pub struct PlaceBatchOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x,
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y,
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Batch::INIT_SPACE,
+        seeds = [b"batch", config.key().as_ref(), slot.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + BatchOrder::INIT_SPACE,
+        seeds = [b"order", batch.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub order: Box<Account<'info, BatchOrder>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceBatchOrder<'info> {
+    // Escrows amount_in for the batch clearing after `slot`, a user adding to the same
+    // batch again tops up their existing order and its minimum. The crank picks when the
+    // batch clears, so amount_out_min bounds what the order accepts at any price.
+    pub fn place_batch_order(
+        &mut self,
+        slot: u64,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        bumps: &PlaceBatchOrderBumps,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(self.config.batch_auction, AmmError::BatchAuctionDisabled);
+        assert_non_zero!([amount_in]);
+
+        let current_slot = Clock::get()?.slot;
+        require!(
+            slot >= current_slot && slot - current_slot <= Batch::MAX_SLOTS_AHEAD,
+            AmmError::InvalidBatchSlot
+        );
+
+        let is_x = match mint_deposit {
+            m if m == self.mint_x.key() => true,
+            m if m == self.mint_y.key() => false,
+            _ => return Err(AmmError::InvalidInputMint.into()),
+        };
+        let mint_in = match is_x {
+            true => self.mint_x.to_account_info(),
+            false => self.mint_y.to_account_info(),
+        };
+
+        // the swap fee is taken up front, every order in the batch then trades at one price
+        let vault_amount_in = amount_in - get_transfer_fee(&mint_in, amount_in)?;
+        let fee = curve::fee_amount(vault_amount_in, self.config.fee).map_err(AmmError::from)?;
+        let amount_in_net = vault_amount_in - fee;
+        assert_non_zero!([amount_in_net]);
+
+        if self.batch.config == Pubkey::default() {
+            self.batch.set_inner(Batch {
+                config: self.config.key(),
+                slot,
+                payer: self.user.key(),
+                amount_in_x: 0,
+                amount_in_y: 0,
+                fee_x: 0,
+                fee_y: 0,
+                amount_out_x: 0,
+                amount_out_y: 0,
+                claimed_x: 0,
+                claimed_y: 0,
+                cleared: false,
+                open_orders: 0,
+                bump: bumps.batch,
+            });
+        }
+        if self.order.batch == Pubkey::default() {
+            self.order.set_inner(BatchOrder {
+                batch: self.batch.key(),
+                owner: self.user.key(),
+                amount_in_x: 0,
+                amount_in_y: 0,
+                min_out_x: 0,
+                min_out_y: 0,
+                bump: bumps.order,
+            });
+            self.batch.open_orders = self
+                .batch
+                .open_orders
+                .checked_add(1)
+                .ok_or(AmmError::Overflow)?;
+        }
+
+        let batch: &mut Batch = &mut self.batch;
+        let order: &mut BatchOrder = &mut self.order;
+        let (batch_in, batch_fee, order_in, order_min_out, escrow) = match is_x {
+            true => (
+                &mut batch.amount_in_x,
+                &mut batch.fee_x,
+                &mut order.amount_in_x,
+                &mut order.min_out_y,
+                &mut self.pooldata.batch_x,
+            ),
+            false => (
+                &mut batch.amount_in_y,
+                &mut batch.fee_y,
+                &mut order.amount_in_y,
+                &mut order.min_out_x,
+                &mut self.pooldata.batch_y,
+            ),
+        };
+        *batch_in = batch_in
+            .checked_add(amount_in_net)
+            .ok_or(AmmError::Overflow)?;
+        *batch_fee = batch_fee.checked_add(fee).ok_or(AmmError::Overflow)?;
+        *order_in = order_in
+            .checked_add(amount_in_net)
+            .ok_or(AmmError::Overflow)?;
+        *order_min_out = order_min_out
+            .checked_add(amount_out_min)
+            .ok_or(AmmError::Overflow)?;
+        *escrow = escrow
+            .checked_add(vault_amount_in)
+            .ok_or(AmmError::Overflow)?;
+
+        self.deposit_token(is_x, amount_in)?;
+
+        emit!(BatchOrderPlaced {
+            config: self.config.key(),
+            batch: self.batch.key(),
+            user: self.user.key(),
+            slot,
+            mint_in: mint_deposit,
+            amount_in,
+            amount_in_net,
+            amount_out_min,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (mint, decimals, from, to, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.user_ata_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.user_ata_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from,
+            mint,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(token_program, accounts);
+        transfer_checked(ctx, amount, decimals)
+    }
+}
@@ -0,0 +1,190 @@
+use crate::assert_non_zero;
+use crate::errors::AmmError;
+use crate::events::LimitOrderPlaced;
+use crate::{
+    assert_not_locked,
+    helpers::get_transfer_fee,
+    states::{Config, LimitOrder, PoolData},
+};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct PlaceLimitOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x,
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y,
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + LimitOrder::INIT_SPACE,
+        seeds = [b"limit_order", config.key().as_ref(), user.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub order: Box<Account<'info, LimitOrder>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceLimitOrder<'info> {
+    // Escrows amount_in to be sold once the pool pays min_ratio, along with keeper_reward
+    // lamports for whoever fills the order. Both ATAs are created here so a fill never has
+    // to pay for the owner's output account.
+    pub fn place_limit_order(
+        &mut self,
+        id: u64,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        min_ratio: u128,
+        keeper_reward: u64,
+        bumps: &PlaceLimitOrderBumps,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+        assert_non_zero!([amount_in]);
+        require!(min_ratio > 0, AmmError::ZeroBalance);
+
+        let is_x = match mint_deposit {
+            m if m == self.mint_x.key() => true,
+            m if m == self.mint_y.key() => false,
+            _ => return Err(AmmError::InvalidInputMint.into()),
+        };
+        let mint_in = match is_x {
+            true => self.mint_x.to_account_info(),
+            false => self.mint_y.to_account_info(),
+        };
+        let escrow_amount = amount_in - get_transfer_fee(&mint_in, amount_in)?;
+        assert_non_zero!([escrow_amount]);
+
+        let escrow = match is_x {
+            true => &mut self.pooldata.limit_orders_x,
+            false => &mut self.pooldata.limit_orders_y,
+        };
+        *escrow = escrow
+            .checked_add(escrow_amount)
+            .ok_or(AmmError::Overflow)?;
+
+        self.order.set_inner(LimitOrder {
+            config: self.config.key(),
+            owner: self.user.key(),
+            id,
+            mint_in: mint_deposit,
+            amount_in: escrow_amount,
+            min_ratio,
+            keeper_reward,
+            bump: bumps.order,
+        });
+
+        self.deposit_token(is_x, amount_in)?;
+        if keeper_reward > 0 {
+            let accounts = Transfer {
+                from: self.user.to_account_info(),
+                to: self.order.to_account_info(),
+            };
+            let ctx = CpiContext::new(self.system_program.to_account_info(), accounts);
+            transfer(ctx, keeper_reward)?;
+        }
+
+        emit!(LimitOrderPlaced {
+            config: self.config.key(),
+            order: self.order.key(),
+            user: self.user.key(),
+            mint_in: mint_deposit,
+            amount_in: escrow_amount,
+            min_ratio,
+            keeper_reward,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (mint, decimals, from, to, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.user_ata_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.user_ata_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from,
+            mint,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(token_program, accounts);
+        transfer_checked(ctx, amount, decimals)
+    }
+}
@@ -0,0 +1,114 @@
+use crate::errors::AmmError;
+use crate::helpers::get_transfer_fee;
+use crate::states::{Config, PoolData};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"mint_lp", config.key().as_ref()],
+        bump,
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+// Returned through return data so simulations get the exact swap outcome
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    // output of the pool's curve before the fairness clamp
+    pub amount_out_raw: u64,
+    // output after the fairness clamp and any transfer fee, what the user would receive
+    pub amount_out: u64,
+    // execution ratio after the fairness clamp
+    pub ratio: u128,
+    // fairness window the swap would execute in
+    pub window_start_slot: u64,
+    pub window_slots: u64,
+}
+
+impl<'info> Quote<'info> {
+    pub fn quote(
+        &self,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+    ) -> Result<SwapQuote> {
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+        let is_buying_x = match mint_deposit {
+            m if m == self.mint_x.key() => false,
+            m if m == self.mint_y.key() => true,
+            _ => return Err(AmmError::InvalidInputMint.into()),
+        };
+        let (mint_in, mint_out) = match is_buying_x {
+            true => (self.mint_y.to_account_info(), self.mint_x.to_account_info()),
+            false => (self.mint_x.to_account_info(), self.mint_y.to_account_info()),
+        };
+        let vault_amount_in = amount_in - get_transfer_fee(&mint_in, amount_in)?;
+
+        let reserves = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+
+        // price against a copy so the pool's fairness state is left untouched
+        let mut pooldata = PoolData::clone(&self.pooldata);
+        let clock = Clock::get()?;
+        let res = pooldata.fair_swap(
+            reserves,
+            self.config.curve(clock.unix_timestamp),
+            self.config.fee,
+            is_buying_x,
+            vault_amount_in,
+            0,
+            clock.slot,
+        )?;
+        let amount_out = res.withdraw - get_transfer_fee(&mint_out, res.withdraw)?;
+        require!(amount_out >= amount_out_min, AmmError::SlippageExceeded);
+
+        Ok(SwapQuote {
+            amount_in,
+            amount_out_raw: res.raw_withdraw,
+            amount_out,
+            ratio: res.ratio,
+            window_start_slot: pooldata.last_slot,
+            window_slots: pooldata.window_slots,
+        })
+    }
+}
@@ -0,0 +1,163 @@
+use crate::errors::AmmError;
+use crate::events::CommitmentRefunded;
+use crate::states::{Config, PoolData, SwapCommitment};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct RefundCommitment<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: this is safe
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump = config.bump_auth,
+    )]
+    pub auth: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x,
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y,
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        mut,
+        close = user,
+        has_one = config,
+        seeds = [b"commitment", config.key().as_ref(), user.key().as_ref(), commitment.hash.as_ref()],
+        bump = commitment.bump,
+    )]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundCommitment<'info> {
+    // Returns the whole escrow of a commitment that was never revealed. Works on a locked
+    // pool too, the escrow was never part of the reserves.
+    pub fn refund_commitment(&mut self) -> Result<()> {
+        require!(
+            self.commitment.is_expired(Clock::get()?.slot),
+            AmmError::CommitmentNotExpired
+        );
+
+        let (amount_x, amount_y) = (self.commitment.amount_x, self.commitment.amount_y);
+        self.pooldata.committed_x = self
+            .pooldata
+            .committed_x
+            .checked_sub(amount_x)
+            .ok_or(AmmError::Underflow)?;
+        self.pooldata.committed_y = self
+            .pooldata
+            .committed_y
+            .checked_sub(amount_y)
+            .ok_or(AmmError::Underflow)?;
+
+        if amount_x > 0 {
+            self.withdraw_token(true, amount_x)?;
+        }
+        if amount_y > 0 {
+            self.withdraw_token(false, amount_y)?;
+        }
+
+        emit!(CommitmentRefunded {
+            config: self.config.key(),
+            commitment: self.commitment.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
+        let signer_seeds = &[&seeds[..]];
+
+        let (mint, decimals, vault, ata, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.vault_x.to_account_info(),
+                self.user_ata_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.vault_y.to_account_info(),
+                self.user_ata_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from: vault,
+            to: ata,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+        transfer_checked(ctx, amount, decimals)
+    }
+}
@@ -0,0 +1,261 @@
+use crate::assert_non_zero;
+use crate::errors::AmmError;
+use crate::events::{CommitmentRefunded, Swapped};
+use crate::{
+    assert_not_locked,
+    helpers::get_transfer_fee,
+    states::{Config, PoolData, SwapCommitment},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct RevealSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: this is safe
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump = config.bump_auth,
+    )]
+    pub auth: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x,
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y,
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config".as_ref(), mint_x.key().as_ref(), mint_y.key().as_ref(), config.seed.to_le_bytes().as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    #[account(
+        mut,
+        close = user,
+        has_one = config,
+        seeds = [b"commitment", config.key().as_ref(), user.key().as_ref(), commitment.hash.as_ref()],
+        bump = commitment.bump,
+    )]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RevealSwap<'info> {
+    // Checks the parameters against the commitment and swaps amount_in out of the escrow
+    // like a regular swap. amount_in is counted as escrowed, after any transfer fee.
+    pub fn reveal_swap(
+        &mut self,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+        assert_non_zero!([amount_in, amount_out_min]);
+
+        let clock = Clock::get()?;
+        require!(
+            self.commitment.can_reveal(clock.slot),
+            AmmError::RevealNotAllowed
+        );
+        require!(
+            SwapCommitment::hash(&mint_deposit, amount_in, amount_out_min, &salt)
+                == self.commitment.hash,
+            AmmError::CommitmentMismatch
+        );
+
+        let is_buying_x = match mint_deposit {
+            m if m == self.mint_x.key() => false,
+            m if m == self.mint_y.key() => true,
+            _ => return Err(AmmError::InvalidInputMint.into()),
+        };
+        let escrow_in = match is_buying_x {
+            true => self.commitment.amount_y,
+            false => self.commitment.amount_x,
+        };
+        require!(amount_in <= escrow_in, AmmError::InsufficientBalance);
+
+        // priced before the escrow is released, so the input isn't counted as reserves yet
+        let reserves = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        self.pooldata
+            .update_oracle(reserves.0, reserves.1, curve, clock.unix_timestamp)?;
+        let res = self.pooldata.fair_swap(
+            reserves,
+            curve,
+            self.config.fee,
+            is_buying_x,
+            amount_in,
+            0,
+            clock.slot,
+        )?;
+        self.pooldata
+            .accrue_protocol_fee(!is_buying_x, res.fee, self.config.protocol_fee_bps)?;
+
+        let surplus = res.raw_withdraw - res.withdraw;
+        self.pooldata
+            .capture_surplus(is_buying_x, surplus, self.config.surplus_policy)?;
+        msg!("surplus: {}", surplus);
+
+        let mint_out = match is_buying_x {
+            true => self.mint_x.to_account_info(),
+            false => self.mint_y.to_account_info(),
+        };
+        let amount_received = res.withdraw - get_transfer_fee(&mint_out, res.withdraw)?;
+        require!(
+            amount_received >= amount_out_min,
+            AmmError::SlippageExceeded
+        );
+        assert_non_zero!([res.deposit, amount_received]);
+
+        // the whole escrow leaves the commitment, whatever wasn't swapped goes back
+        self.pooldata.committed_x = self
+            .pooldata
+            .committed_x
+            .checked_sub(self.commitment.amount_x)
+            .ok_or(AmmError::Underflow)?;
+        self.pooldata.committed_y = self
+            .pooldata
+            .committed_y
+            .checked_sub(self.commitment.amount_y)
+            .ok_or(AmmError::Underflow)?;
+        let (refund_x, refund_y) = match is_buying_x {
+            true => (
+                self.commitment.amount_x,
+                self.commitment.amount_y - amount_in,
+            ),
+            false => (
+                self.commitment.amount_x - amount_in,
+                self.commitment.amount_y,
+            ),
+        };
+        let (out_x, out_y) = match is_buying_x {
+            true => (res.withdraw, 0),
+            false => (0, res.withdraw),
+        };
+
+        // one transfer per token covers both the swap output and the refund
+        let payout_x = out_x.checked_add(refund_x).ok_or(AmmError::Overflow)?;
+        let payout_y = out_y.checked_add(refund_y).ok_or(AmmError::Overflow)?;
+        if payout_x > 0 {
+            self.withdraw_token(true, payout_x)?;
+        }
+        if payout_y > 0 {
+            self.withdraw_token(false, payout_y)?;
+        }
+
+        emit!(Swapped {
+            config: self.config.key(),
+            user: self.user.key(),
+            mint_in: mint_deposit,
+            amount_in,
+            amount_out_raw: res.raw_withdraw,
+            amount_out: res.withdraw,
+            ratio: res.ratio,
+            buying_x_high: self.pooldata.buying_x_high,
+            buying_y_high: self.pooldata.buying_y_high,
+            window_start_slot: self.pooldata.last_slot,
+        });
+        if refund_x > 0 || refund_y > 0 {
+            emit!(CommitmentRefunded {
+                config: self.config.key(),
+                commitment: self.commitment.key(),
+                user: self.user.key(),
+                amount_x: refund_x,
+                amount_y: refund_y,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
+        let signer_seeds = &[&seeds[..]];
+
+        let (mint, decimals, vault, ata, token_program) = match is_x {
+            true => (
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.vault_x.to_account_info(),
+                self.user_ata_x.to_account_info(),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.vault_y.to_account_info(),
+                self.user_ata_y.to_account_info(),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from: vault,
+            to: ata,
+            authority: self.auth.to_account_info(),
+            mint,
+        };
+
+        let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+        transfer_checked(ctx, amount, decimals)
+    }
+}
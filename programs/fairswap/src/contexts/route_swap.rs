@@ -0,0 +1,260 @@
+use crate::errors::AmmError;
+use crate::events::Swapped;
+use crate::helpers::get_transfer_fee;
+use crate::states::{Config, Deadline, PoolData};
+use crate::{assert_non_zero, assert_not_expired};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{transfer_checked, Mint, TokenAccount, TransferChecked},
+};
+
+// Accounts each hop expects in remaining_accounts, in this order:
+// config, pooldata (mut), auth, mint_x, mint_y, vault_x (mut), vault_y (mut),
+// user_ata_x (mut), user_ata_y (mut)
+pub const ROUTE_HOP_ACCOUNTS: usize = 9;
+
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+struct RouteHop<'info> {
+    config: Account<'info, Config>,
+    pooldata: Box<Account<'info, PoolData>>,
+    auth: AccountInfo<'info>,
+    mint_x: InterfaceAccount<'info, Mint>,
+    mint_y: InterfaceAccount<'info, Mint>,
+    vault_x: InterfaceAccount<'info, TokenAccount>,
+    vault_y: InterfaceAccount<'info, TokenAccount>,
+    user_ata_x: InterfaceAccount<'info, TokenAccount>,
+    user_ata_y: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> RouteSwap<'info> {
+    pub fn route_swap(
+        &self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        mint_in: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
+        assert_non_zero!([amount_in, amount_out_min]);
+        assert_not_expired!(deadline);
+        let hops = remaining_accounts.chunks_exact(ROUTE_HOP_ACCOUNTS);
+        require!(
+            hops.len() > 0 && hops.remainder().is_empty(),
+            AmmError::InvalidRoute
+        );
+
+        let clock = Clock::get()?;
+        let (mut mint_deposit, mut amount) = (mint_in, amount_in);
+
+        for accounts in hops {
+            let mut hop = self.load_hop(accounts)?;
+            require!(!hop.config.locked, AmmError::PoolLocked);
+            require!(!hop.config.batch_auction, AmmError::BatchAuctionOnly);
+
+            let is_buying_x = match mint_deposit {
+                m if m == hop.mint_x.key() => false,
+                m if m == hop.mint_y.key() => true,
+                _ => return Err(AmmError::InvalidRoute.into()),
+            };
+            let (mint_in, mint_out) = match is_buying_x {
+                true => (hop.mint_y.to_account_info(), hop.mint_x.to_account_info()),
+                false => (hop.mint_x.to_account_info(), hop.mint_y.to_account_info()),
+            };
+            let vault_amount = amount - get_transfer_fee(&mint_in, amount)?;
+
+            let reserves = hop
+                .pooldata
+                .reserves(hop.vault_x.amount, hop.vault_y.amount)?;
+            let curve = hop.config.curve(clock.unix_timestamp);
+            hop.pooldata
+                .update_oracle(reserves.0, reserves.1, curve, clock.unix_timestamp)?;
+
+            // per-hop slippage is not checked, only the end-to-end output is
+            let res = hop.pooldata.fair_swap(
+                reserves,
+                curve,
+                hop.config.fee,
+                is_buying_x,
+                vault_amount,
+                0,
+                clock.slot,
+            )?;
+            assert_non_zero!([res.deposit, res.withdraw]);
+
+            hop.pooldata
+                .accrue_protocol_fee(!is_buying_x, res.fee, hop.config.protocol_fee_bps)?;
+            let surplus = res.raw_withdraw - res.withdraw;
+            hop.pooldata
+                .capture_surplus(is_buying_x, surplus, hop.config.surplus_policy)?;
+            hop.pooldata.exit(&crate::ID)?;
+
+            self.transfer_hop(&hop, is_buying_x, amount, res.withdraw)?;
+            emit!(Swapped {
+                config: hop.config.key(),
+                user: self.user.key(),
+                mint_in: mint_deposit,
+                amount_in: amount,
+                amount_out_raw: res.raw_withdraw,
+                amount_out: res.withdraw,
+                ratio: res.ratio,
+                buying_x_high: hop.pooldata.buying_x_high,
+                buying_y_high: hop.pooldata.buying_y_high,
+                window_start_slot: hop.pooldata.last_slot,
+            });
+
+            mint_deposit = match is_buying_x {
+                true => hop.mint_x.key(),
+                false => hop.mint_y.key(),
+            };
+            // the next hop can only spend what landed after any transfer fee
+            amount = res.withdraw - get_transfer_fee(&mint_out, res.withdraw)?;
+        }
+
+        msg!("route amount out: {}", amount);
+        require!(amount >= amount_out_min, AmmError::SlippageExceeded);
+        Ok(())
+    }
+
+    fn load_hop(&self, accounts: &'info [AccountInfo<'info>]) -> Result<RouteHop<'info>> {
+        let config = Account::<Config>::try_from(&accounts[0])?;
+        let config_key = config.key();
+        let seed = config.seed.to_le_bytes();
+        let expected_config = Pubkey::create_program_address(
+            &[
+                b"config",
+                config.mint_x.as_ref(),
+                config.mint_y.as_ref(),
+                seed.as_ref(),
+                &[config.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::InvalidRoute)?;
+        require_keys_eq!(config_key, expected_config, AmmError::InvalidRoute);
+
+        let pooldata = Box::new(Account::<PoolData>::try_from(&accounts[1])?);
+        let expected_pooldata = Pubkey::create_program_address(
+            &[b"pooldata", config_key.as_ref(), &[pooldata.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::InvalidRoute)?;
+        require_keys_eq!(pooldata.key(), expected_pooldata, AmmError::InvalidRoute);
+
+        let auth = accounts[2].clone();
+        let expected_auth = Pubkey::create_program_address(
+            &[b"auth", config_key.as_ref(), &[config.bump_auth]],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::InvalidRoute)?;
+        require_keys_eq!(auth.key(), expected_auth, AmmError::InvalidRoute);
+
+        let mint_x = InterfaceAccount::<Mint>::try_from(&accounts[3])?;
+        let mint_y = InterfaceAccount::<Mint>::try_from(&accounts[4])?;
+        require_keys_eq!(mint_x.key(), config.mint_x, AmmError::InvalidRoute);
+        require_keys_eq!(mint_y.key(), config.mint_y, AmmError::InvalidRoute);
+
+        let vault_x = InterfaceAccount::<TokenAccount>::try_from(&accounts[5])?;
+        let vault_y = InterfaceAccount::<TokenAccount>::try_from(&accounts[6])?;
+        let (expected_vault_x, _) = Pubkey::find_program_address(
+            &[b"vault", config_key.as_ref(), config.mint_x.as_ref()],
+            &crate::ID,
+        );
+        let (expected_vault_y, _) = Pubkey::find_program_address(
+            &[b"vault", config_key.as_ref(), config.mint_y.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(vault_x.key(), expected_vault_x, AmmError::InvalidRoute);
+        require_keys_eq!(vault_y.key(), expected_vault_y, AmmError::InvalidRoute);
+
+        let user_ata_x = InterfaceAccount::<TokenAccount>::try_from(&accounts[7])?;
+        let user_ata_y = InterfaceAccount::<TokenAccount>::try_from(&accounts[8])?;
+        require!(
+            user_ata_x.owner == self.user.key() && user_ata_x.mint == config.mint_x,
+            AmmError::InvalidRoute
+        );
+        require!(
+            user_ata_y.owner == self.user.key() && user_ata_y.mint == config.mint_y,
+            AmmError::InvalidRoute
+        );
+
+        Ok(RouteHop {
+            config,
+            pooldata,
+            auth,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            user_ata_x,
+            user_ata_y,
+        })
+    }
+
+    fn transfer_hop(
+        &self,
+        hop: &RouteHop<'info>,
+        is_buying_x: bool,
+        deposit: u64,
+        withdraw: u64,
+    ) -> Result<()> {
+        let (mint_in, user_in, vault_in, mint_out, vault_out, user_out) = match is_buying_x {
+            true => (
+                &hop.mint_y,
+                &hop.user_ata_y,
+                &hop.vault_y,
+                &hop.mint_x,
+                &hop.vault_x,
+                &hop.user_ata_x,
+            ),
+            false => (
+                &hop.mint_x,
+                &hop.user_ata_x,
+                &hop.vault_x,
+                &hop.mint_y,
+                &hop.vault_y,
+                &hop.user_ata_y,
+            ),
+        };
+
+        let accounts = TransferChecked {
+            from: user_in.to_account_info(),
+            mint: mint_in.to_account_info(),
+            to: vault_in.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+        let ctx = CpiContext::new(self.token_program_for(mint_in), accounts);
+        transfer_checked(ctx, deposit, mint_in.decimals)?;
+
+        let config_key = hop.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[hop.config.bump_auth]];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = TransferChecked {
+            from: vault_out.to_account_info(),
+            mint: mint_out.to_account_info(),
+            to: user_out.to_account_info(),
+            authority: hop.auth.clone(),
+        };
+        let ctx =
+            CpiContext::new_with_signer(self.token_program_for(mint_out), accounts, signer_seeds);
+        transfer_checked(ctx, withdraw, mint_out.decimals)
+    }
+
+    // Hops may mix legacy and Token-2022 mints, so pick the program that owns each one
+    fn token_program_for(&self, mint: &InterfaceAccount<'info, Mint>) -> AccountInfo<'info> {
+        match *mint.to_account_info().owner == self.token_program_2022.key() {
+            true => self.token_program_2022.to_account_info(),
+            false => self.token_program.to_account_info(),
+        }
+    }
+}
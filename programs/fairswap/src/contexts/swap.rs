@@ -1,19 +1,19 @@
 use crate::assert_non_zero;
 use crate::errors::AmmError;
+use crate::events::Swapped;
 use crate::{
-    assert_not_locked,
-    helpers::calculate_limit_price,
-    states::{Config, PoolData},
+    assert_not_expired, assert_not_locked,
+    helpers::{
+        calculate_amount_at_ratio, calculate_amount_in, get_transfer_fee, get_transfer_inverse_fee,
+    },
+    states::{Config, Deadline, FairSwapResult, PoolData},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer_checked, TransferChecked},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use constant_product_curve::{ConstantProduct, LiquidityPair};
-
 #[derive(Accounts)]
 pub struct Swap<'info> {
     #[account(mut)]
@@ -24,7 +24,7 @@ pub struct Swap<'info> {
 
     /// CHECK: this is safe
     #[account(
-        seeds = [b"auth"],
+        seeds = [b"auth", config.key().as_ref()],
         bump = config.bump_auth,
     )]
     pub auth: UncheckedAccount<'info>,
@@ -34,7 +34,7 @@ pub struct Swap<'info> {
         payer = user,
         associated_token::mint = mint_x,
         associated_token::authority = user,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_x,
     )]
     pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -43,23 +43,27 @@ pub struct Swap<'info> {
         payer = user,
         associated_token::mint = mint_y,
         associated_token::authority = user,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_y,
     )]
     pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
-        associated_token::mint = mint_x,
-        associated_token::authority = auth,
-        associated_token::token_program = token_program,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
     )]
     pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
-        associated_token::mint = mint_y,
-        associated_token::authority = auth,
-        associated_token::token_program = token_program,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
     )]
     pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -85,7 +89,8 @@ pub struct Swap<'info> {
     )]
     pub pooldata: Box<Account<'info, PoolData>>,
 
-    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -96,113 +101,191 @@ impl<'info> Swap<'info> {
         mint_deposit: Pubkey,
         amount_in: u64,
         amount_out_min: u64,
+        deadline: Deadline,
     ) -> Result<()> {
         assert_not_locked!(self.config.locked);
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+        assert_not_expired!(deadline);
         assert_non_zero!([amount_in, amount_out_min]);
 
-        let mut curve = ConstantProduct::init(
-            self.vault_x.amount,
-            self.vault_y.amount,
-            self.mint_lp.supply,
-            self.config.fee,
-            None,
-        )
-        .map_err(AmmError::from)?;
+        let (mint_withdraw, is_buying_x) = self.swap_direction(mint_deposit)?;
+        let (mint_in, mint_out) = self.swap_mints(is_buying_x);
 
-        let (p, mint_withdraw, is_buying_x) = match mint_deposit {
-            m if m == self.mint_x.key() => (LiquidityPair::X, self.mint_y.key(), false),
-            m if m == self.mint_y.key() => (LiquidityPair::Y, self.mint_x.key(), true),
-            _ => return Err(AmmError::InvalidInputMint.into()),
-        };
-        msg!("is_buying_x: {}", is_buying_x);
-        let res = curve
-            .swap(p, amount_in, amount_out_min)
-            .map_err(AmmError::from)?;
-
-        let mut current_ratio =
-            calculate_limit_price(res.deposit, res.withdraw, self.pooldata.precision)?;
-        msg!("current_ratio: {}", current_ratio);
-
-        let current_slot = Clock::get()?.slot;
-        if current_slot != self.pooldata.last_slot {
-            msg!("Updating pool data");
-            self.pooldata.last_slot = current_slot;
-            if is_buying_x {
-                self.pooldata.buying_x_high = Some(current_ratio);
-                self.pooldata.buying_y_high = Some(calculate_limit_price(
-                    self.vault_x.amount,
-                    self.vault_y.amount,
-                    self.pooldata.precision,
-                )?);
-                msg!("updated buying_x_high");
-            } else {
-                self.pooldata.buying_x_high = Some(calculate_limit_price(
-                    self.vault_y.amount,
-                    self.vault_x.amount,
-                    self.pooldata.precision,
-                )?);
-                self.pooldata.buying_y_high = Some(current_ratio);
-                msg!("updated buying_y_high");
-            }
-        }
-        // msg!("current_slot: {}", current_slot);
-
-        // Determine the price limit based on whether we are buying X or Y
-        if is_buying_x {
-            if current_ratio > self.pooldata.buying_x_high.unwrap() {
-                self.pooldata.buying_x_high = Some(current_ratio);
-                msg!("updated buying_x_high");
-            } else if current_ratio < self.pooldata.buying_x_high.unwrap() {
-                current_ratio = self.pooldata.buying_x_high.unwrap();
-                msg!("current_ratio: {}", current_ratio);
-            }
-        } else {
-            if current_ratio > self.pooldata.buying_y_high.unwrap() {
-                self.pooldata.buying_y_high = Some(current_ratio);
-                msg!("updated buying_y_high");
-            } else if current_ratio < self.pooldata.buying_y_high.unwrap() {
-                current_ratio = self.pooldata.buying_y_high.unwrap();
-                msg!("current_ratio: {}", current_ratio);
-            }
-        }
+        // the curve only sees what lands in the vault after any transfer fee
+        let vault_amount_in = amount_in - get_transfer_fee(&mint_in, amount_in)?;
+        let res = self.fair_swap(is_buying_x, vault_amount_in, 0)?;
+        self.pooldata
+            .accrue_protocol_fee(!is_buying_x, res.fee, self.config.protocol_fee_bps)?;
 
-        msg!("og withdraw amount: {}", res.withdraw);
-        let withdraw_amount = (res.deposit as u128)
-            .checked_mul(
-                10u128
-                    .checked_pow(self.pooldata.precision as u32)
-                    .ok_or(AmmError::InvalidPrecision)?,
-            )
-            .ok_or(AmmError::Overflow)?
-            .checked_div(current_ratio)
-            .ok_or(AmmError::Overflow)? as u64;
-        // let withdraw_amount = current_ratio
-        //     .checked_mul(res.deposit as u128)
-        //     .ok_or(AmmError::Overflow)?
-        //     .checked_div(
-        //         10u128
-        //             .checked_pow(self.pooldata.precision as u32)
-        //             .ok_or(AmmError::InvalidPrecision)?,
-        //     )
-        //     .ok_or(AmmError::Overflow)? as u64;
-        msg!("withdraw_amount: {}", withdraw_amount);
+        let surplus = res.raw_withdraw - res.withdraw;
+        self.pooldata
+            .capture_surplus(is_buying_x, surplus, self.config.surplus_policy)?;
+        msg!("surplus: {}", surplus);
 
+        let amount_received = res.withdraw - get_transfer_fee(&mint_out, res.withdraw)?;
         require!(
-            withdraw_amount >= amount_out_min,
+            amount_received >= amount_out_min,
             AmmError::SlippageExceeded
         );
-        assert_non_zero!([res.deposit, withdraw_amount]);
+        assert_non_zero!([res.deposit, amount_received]);
+
+        self.deposit_token(mint_deposit, amount_in)?;
+        self.withdraw_token(mint_withdraw, res.withdraw)?;
+        self.emit_swapped(mint_deposit, amount_in, &res);
+        Ok(())
+    }
+
+    pub fn swap_exact_out(
+        &mut self,
+        mint_deposit: Pubkey,
+        amount_out: u64,
+        max_amount_in: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
+        assert_not_locked!(self.config.locked);
+        require!(!self.config.batch_auction, AmmError::BatchAuctionOnly);
+        assert_not_expired!(deadline);
+        assert_non_zero!([amount_out, max_amount_in]);
+
+        let (mint_withdraw, is_buying_x) = self.swap_direction(mint_deposit)?;
+        let (mint_in, mint_out) = self.swap_mints(is_buying_x);
+
+        // the vault has to send enough for amount_out to arrive after any transfer fee
+        let vault_amount_out = amount_out
+            .checked_add(get_transfer_inverse_fee(&mint_out, amount_out)?)
+            .ok_or(AmmError::Overflow)?;
+
+        let (reserve_x, reserve_y) = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let clock = Clock::get()?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        let (reserve_in, reserve_out, curve) = match is_buying_x {
+            true => (reserve_y, reserve_x, curve.reversed()),
+            false => (reserve_x, reserve_y, curve),
+        };
 
-        self.deposit_token(mint_deposit, res.deposit)?;
-        self.withdraw_token(mint_withdraw, withdraw_amount)?;
+        // input the curve needs to release vault_amount_out
+        let curve_amount_in = calculate_amount_in(
+            curve,
+            reserve_in,
+            reserve_out,
+            vault_amount_out,
+            self.config.fee,
+        )?;
+
+        // input needed to receive amount_out at the window's high-water mark
+        let fair_amount_in = match self.pooldata.is_window_expired(clock.slot) {
+            true => 0,
+            false => {
+                let high = match is_buying_x {
+                    true => self.pooldata.buying_x_high,
+                    false => self.pooldata.buying_y_high,
+                };
+                match high {
+                    Some(ratio) => {
+                        calculate_amount_at_ratio(vault_amount_out, ratio, self.pooldata.precision)?
+                    }
+                    None => 0,
+                }
+            }
+        };
+
+        let vault_amount_in = curve_amount_in.max(fair_amount_in);
+        let amount_in = vault_amount_in
+            .checked_add(get_transfer_inverse_fee(&mint_in, vault_amount_in)?)
+            .ok_or(AmmError::Overflow)?;
+        msg!("amount_in: {}", amount_in);
+        require!(amount_in <= max_amount_in, AmmError::SlippageExceeded);
+
+        let res = self.fair_swap(is_buying_x, vault_amount_in, vault_amount_out)?;
+        self.pooldata
+            .accrue_protocol_fee(!is_buying_x, res.fee, self.config.protocol_fee_bps)?;
+        require!(res.withdraw >= vault_amount_out, AmmError::SlippageExceeded);
+
+        // rounding leftovers above vault_amount_out are kept like any other surplus
+        let surplus = res.raw_withdraw - vault_amount_out;
+        self.pooldata
+            .capture_surplus(is_buying_x, surplus, self.config.surplus_policy)?;
+        msg!("surplus: {}", surplus);
+
+        self.deposit_token(mint_deposit, amount_in)?;
+        self.withdraw_token(mint_withdraw, vault_amount_out)?;
+        self.emit_swapped(
+            mint_deposit,
+            amount_in,
+            &FairSwapResult {
+                withdraw: vault_amount_out,
+                ..res
+            },
+        );
         Ok(())
     }
 
+    // Returns the withdraw mint and whether the user is buying X
+    pub fn swap_direction(&self, mint_deposit: Pubkey) -> Result<(Pubkey, bool)> {
+        match mint_deposit {
+            m if m == self.mint_x.key() => Ok((self.mint_y.key(), false)),
+            m if m == self.mint_y.key() => Ok((self.mint_x.key(), true)),
+            _ => Err(AmmError::InvalidInputMint.into()),
+        }
+    }
+
+    // Returns the (input, output) mint accounts for a swap direction
+    pub fn swap_mints(&self, is_buying_x: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        match is_buying_x {
+            true => (self.mint_y.to_account_info(), self.mint_x.to_account_info()),
+            false => (self.mint_x.to_account_info(), self.mint_y.to_account_info()),
+        }
+    }
+
+    // Prices amount_in against the pool's current reserves and fairness window
+    pub fn fair_swap(
+        &mut self,
+        is_buying_x: bool,
+        amount_in: u64,
+        amount_out_min: u64,
+    ) -> Result<FairSwapResult> {
+        let reserves = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+
+        let clock = Clock::get()?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        self.pooldata
+            .update_oracle(reserves.0, reserves.1, curve, clock.unix_timestamp)?;
+        self.pooldata.fair_swap(
+            reserves,
+            curve,
+            self.config.fee,
+            is_buying_x,
+            amount_in,
+            amount_out_min,
+            clock.slot,
+        )
+    }
+
+    pub fn emit_swapped(&self, mint_in: Pubkey, amount_in: u64, res: &FairSwapResult) {
+        emit!(Swapped {
+            config: self.config.key(),
+            user: self.user.key(),
+            mint_in,
+            amount_in,
+            amount_out_raw: res.raw_withdraw,
+            amount_out: res.withdraw,
+            ratio: res.ratio,
+            buying_x_high: self.pooldata.buying_x_high,
+            buying_y_high: self.pooldata.buying_y_high,
+            window_start_slot: self.pooldata.last_slot,
+        });
+    }
+
     pub fn deposit_token(&mut self, mint_deposit: Pubkey, amount: u64) -> Result<()> {
-        let mint;
+        let (mint, token_program);
         let (from, to) = match mint_deposit {
             m if m == self.mint_x.key() => {
                 mint = self.mint_x.clone();
+                token_program = self.token_program_x.to_account_info();
                 (
                     self.user_ata_x.to_account_info(),
                     self.vault_x.to_account_info(),
@@ -210,6 +293,7 @@ impl<'info> Swap<'info> {
             }
             m if m == self.mint_y.key() => {
                 mint = self.mint_y.clone();
+                token_program = self.token_program_y.to_account_info();
                 (
                     self.user_ata_y.to_account_info(),
                     self.vault_y.to_account_info(),
@@ -225,16 +309,17 @@ impl<'info> Swap<'info> {
             authority: self.user.to_account_info(),
         };
 
-        let ctx = CpiContext::new(self.token_program.to_account_info(), account);
+        let ctx = CpiContext::new(token_program, account);
 
-        transfer_checked(ctx, amount, 6)
+        transfer_checked(ctx, amount, mint.decimals)
     }
 
     pub fn withdraw_token(&mut self, mint_withdraw: Pubkey, amount: u64) -> Result<()> {
-        let mint;
+        let (mint, token_program);
         let (from, to) = match mint_withdraw {
             m if m == self.mint_x.key() => {
                 mint = self.mint_x.clone();
+                token_program = self.token_program_x.to_account_info();
                 (
                     self.vault_x.to_account_info(),
                     self.user_ata_x.to_account_info(),
@@ -242,6 +327,7 @@ impl<'info> Swap<'info> {
             }
             m if m == self.mint_y.key() => {
                 mint = self.mint_y.clone();
+                token_program = self.token_program_y.to_account_info();
                 (
                     self.vault_y.to_account_info(),
                     self.user_ata_y.to_account_info(),
@@ -267,14 +353,11 @@ impl<'info> Swap<'info> {
         //     &binding_seed.as_ref(),
         //     &[self.config.bump],
         // ];
-        let seeds = &[&b"auth"[..], &[self.config.bump_auth]];
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
         let signer_seeds = &[&seeds[..]];
 
-        let ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            account,
-            signer_seeds,
-        );
-        transfer_checked(ctx, amount, 6)
+        let ctx = CpiContext::new_with_signer(token_program, account, signer_seeds);
+        transfer_checked(ctx, amount, mint.decimals)
     }
 }
@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
 
+use crate::assert_has_authority;
 use crate::errors::AmmError;
-use crate::states::Config;
+use crate::events::{
+    AmpRampUpdated, AuthorityAccepted, AuthorityProposed, AuthorityRenounced, BatchAuctionUpdated,
+    FeeUpdated, PoolLocked, PoolUnlocked, SurplusPolicyUpdated, WindowSlotsUpdated,
+};
+use crate::states::{AmpRamp, Config, CurveType, PoolData, SurplusPolicy};
 
 #[derive(Accounts)]
 pub struct Update<'info> {
@@ -19,25 +24,208 @@ pub struct Update<'info> {
     )]
     pub config: Box<Account<'info, Config>>,
 
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Update<'info> {
     pub fn lock(&mut self) -> Result<()> {
-        require!(
-            self.config.authority == self.admin.key(),
-            AmmError::Unauthorized
-        );
+        assert_has_authority!(self.config, self.admin.key());
         self.config.locked = true;
+
+        emit!(PoolLocked {
+            config: self.config.key(),
+            authority: self.admin.key(),
+        });
         Ok(())
     }
 
     pub fn unlock(&mut self) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        self.config.locked = false;
+
+        emit!(PoolUnlocked {
+            config: self.config.key(),
+            authority: self.admin.key(),
+        });
+        Ok(())
+    }
+
+    pub fn set_window_slots(&mut self, window_slots: u64) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        require!(
+            PoolData::is_valid_window(window_slots),
+            AmmError::InvalidWindowSlots
+        );
+
+        emit!(WindowSlotsUpdated {
+            config: self.config.key(),
+            old_window_slots: self.pooldata.window_slots,
+            new_window_slots: window_slots,
+        });
+        self.pooldata.window_slots = window_slots;
+        Ok(())
+    }
+
+    pub fn set_surplus_policy(&mut self, surplus_policy: SurplusPolicy) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        require!(
+            self.pooldata.pending_surplus_x == 0 && self.pooldata.pending_surplus_y == 0,
+            AmmError::PendingSurplus
+        );
+
+        emit!(SurplusPolicyUpdated {
+            config: self.config.key(),
+            old_surplus_policy: self.config.surplus_policy,
+            new_surplus_policy: surplus_policy,
+        });
+        self.config.surplus_policy = surplus_policy;
+        Ok(())
+    }
+
+    // batches already open still clear and pay out after switching back to direct swaps
+    pub fn set_batch_auction(&mut self, batch_auction: bool) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        // batches clear on the constant product invariant
+        require!(
+            !batch_auction || self.config.curve == CurveType::ConstantProduct,
+            AmmError::UnsupportedCurve
+        );
+        self.config.batch_auction = batch_auction;
+
+        emit!(BatchAuctionUpdated {
+            config: self.config.key(),
+            batch_auction,
+        });
+        Ok(())
+    }
+
+    // The ramp starts from wherever amp is now, so a new ramp can replace one in progress
+    pub fn ramp_amp(&mut self, target_amp: u64, end_ts: i64) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        require!(
+            self.config.curve == CurveType::StableSwap,
+            AmmError::UnsupportedCurve
+        );
+        require!(
+            Config::is_valid_amp(self.config.curve, target_amp),
+            AmmError::InvalidAmp
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let current = self.config.amp.current(now);
+        require!(
+            end_ts >= now.saturating_add(Config::MIN_RAMP_DURATION),
+            AmmError::InvalidAmpRamp
+        );
+        require!(
+            target_amp <= current.saturating_mul(Config::MAX_AMP_CHANGE)
+                && current <= target_amp.saturating_mul(Config::MAX_AMP_CHANGE),
+            AmmError::InvalidAmpRamp
+        );
+
+        self.config.amp = AmpRamp {
+            initial_amp: current,
+            target_amp,
+            start_ts: now,
+            end_ts,
+        };
+        self.emit_amp_ramp();
+        Ok(())
+    }
+
+    pub fn stop_ramp_amp(&mut self) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        require!(
+            self.config.curve == CurveType::StableSwap,
+            AmmError::UnsupportedCurve
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        self.config.amp = AmpRamp::fixed(self.config.amp.current(now));
+        self.emit_amp_ramp();
+        Ok(())
+    }
+
+    fn emit_amp_ramp(&self) {
+        let amp = self.config.amp;
+        emit!(AmpRampUpdated {
+            config: self.config.key(),
+            initial_amp: amp.initial_amp,
+            target_amp: amp.target_amp,
+            start_ts: amp.start_ts,
+            end_ts: amp.end_ts,
+        });
+    }
+
+    pub fn set_fee(&mut self, fee: u16, protocol_fee_bps: u16) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        require!(fee < 10000, AmmError::InvalidFeeSet);
+        require!(protocol_fee_bps <= 10000, AmmError::InvalidFee);
+
+        emit!(FeeUpdated {
+            config: self.config.key(),
+            old_fee: self.config.fee,
+            new_fee: fee,
+            old_protocol_fee_bps: self.config.protocol_fee_bps,
+            new_protocol_fee_bps: protocol_fee_bps,
+        });
+        self.config.fee = fee;
+        self.config.protocol_fee_bps = protocol_fee_bps;
+        Ok(())
+    }
+
+    pub fn propose_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        self.config.pending_authority = Some(new_authority);
+
+        emit!(AuthorityProposed {
+            config: self.config.key(),
+            authority: self.admin.key(),
+            pending_authority: new_authority,
+        });
+        Ok(())
+    }
+
+    // signed by the pending authority rather than the current one
+    pub fn accept_authority(&mut self) -> Result<()> {
         require!(
-            self.config.authority == self.admin.key(),
+            self.config.pending_authority == Some(self.admin.key()),
             AmmError::Unauthorized
         );
-        self.config.locked = false;
+
+        emit!(AuthorityAccepted {
+            config: self.config.key(),
+            old_authority: self.config.authority,
+            new_authority: self.admin.key(),
+        });
+        self.config.authority = Some(self.admin.key());
+        self.config.pending_authority = None;
+        Ok(())
+    }
+
+    // Only the authority can collect protocol fees, so they have to be collected first and
+    // stop accruing once nobody can
+    pub fn renounce_authority(&mut self) -> Result<()> {
+        assert_has_authority!(self.config, self.admin.key());
+        require!(
+            self.pooldata.protocol_fees_x == 0 && self.pooldata.protocol_fees_y == 0,
+            AmmError::ProtocolFeesPending
+        );
+        self.config.authority = None;
+        self.config.pending_authority = None;
+        self.config.protocol_fee_bps = 0;
+
+        emit!(AuthorityRenounced {
+            config: self.config.key(),
+            authority: self.admin.key(),
+        });
         Ok(())
     }
 }
@@ -1,14 +1,20 @@
 use crate::assert_non_zero;
 use crate::errors::AmmError;
-use crate::{assert_not_locked, states::Config};
+use crate::events::LiquidityRemoved;
+use crate::{
+    assert_not_expired, assert_not_locked,
+    helpers::get_transfer_fee,
+    states::{Config, Deadline, PoolData},
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{burn, transfer_checked, Burn, TransferChecked},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{
+        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
 
-use constant_product_curve::ConstantProduct;
+use fairswap_math::curve;
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -20,7 +26,7 @@ pub struct Withdraw<'info> {
 
     /// CHECK: this is safe
     #[account(
-        seeds = [b"auth"],
+        seeds = [b"auth", config.key().as_ref()],
         bump = config.bump_auth,
     )]
     pub auth: UncheckedAccount<'info>,
@@ -29,7 +35,7 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = mint_x,
         associated_token::authority = user,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_x,
     )]
     pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
 
@@ -37,7 +43,7 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = mint_y,
         associated_token::authority = user,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_y,
     )]
     pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
 
@@ -45,23 +51,27 @@ pub struct Withdraw<'info> {
         mut,
         associated_token::mint = mint_lp,
         associated_token::authority = user,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_lp,
     )]
     pub user_ata_lp: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        associated_token::mint = mint_x,
-        associated_token::authority = auth,
-        associated_token::token_program = token_program,
+        seeds = [b"vault", config.key().as_ref(), mint_x.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = auth,
+        token::token_program = token_program_x,
     )]
     pub vault_x: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        associated_token::mint = mint_y,
-        associated_token::authority = auth,
-        associated_token::token_program = token_program,
+        seeds = [b"vault", config.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = auth,
+        token::token_program = token_program_y,
     )]
     pub vault_y: InterfaceAccount<'info, TokenAccount>,
 
@@ -69,6 +79,7 @@ pub struct Withdraw<'info> {
         mut,
         seeds = [b"mint_lp", config.key().as_ref()],
         bump,
+        mint::token_program = token_program_lp,
     )]
     pub mint_lp: InterfaceAccount<'info, Mint>,
 
@@ -80,32 +91,76 @@ pub struct Withdraw<'info> {
     )]
     pub config: Account<'info, Config>,
 
-    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        mut,
+        seeds = [b"pooldata", config.key().as_ref()],
+        bump = pooldata.bump,
+    )]
+    pub pooldata: Box<Account<'info, PoolData>>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub token_program_lp: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Withdraw<'info> {
-    pub fn withdraw(&mut self, amount: u64, min_x: u64, min_y: u64) -> Result<()> {
+    pub fn withdraw(
+        &mut self,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
         require!(!self.config.locked, AmmError::PoolLocked);
         assert_non_zero!([amount, min_x, min_y]);
         assert_not_locked!(self.config.locked);
-
-        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
-            self.vault_x.amount,
-            self.vault_y.amount,
+        assert_not_expired!(deadline);
+
+        let (reserve_x, reserve_y) = self
+            .pooldata
+            .reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let clock = Clock::get()?;
+        let curve = self.config.curve(clock.unix_timestamp);
+        self.pooldata
+            .update_oracle(reserve_x, reserve_y, curve, clock.unix_timestamp)?;
+
+        let (x, y) = curve::withdraw_amounts(reserve_x, reserve_y, self.mint_lp.supply, amount)
+            .map_err(AmmError::from)?;
+        self.pooldata.rescale_window(
+            (reserve_x, reserve_y),
+            curve,
             self.mint_lp.supply,
-            amount,
-            6,
-        )
-        .map_err(AmmError::from)?;
-        let (x, y) = (amounts.x, amounts.y);
-
-        require!(x >= min_x && y >= min_y, AmmError::SlippageExceeded);
+            self.mint_lp
+                .supply
+                .checked_sub(amount)
+                .ok_or(AmmError::Underflow)?,
+            clock.slot,
+        )?;
+
+        // slippage is checked against what reaches the user after any transfer fee
+        let received_x = x - get_transfer_fee(&self.mint_x.to_account_info(), x)?;
+        let received_y = y - get_transfer_fee(&self.mint_y.to_account_info(), y)?;
+        require!(
+            received_x >= min_x && received_y >= min_y,
+            AmmError::SlippageExceeded
+        );
 
         self.withdraw_tokens(true, x)?;
         self.withdraw_tokens(false, y)?;
-        self.burn_lp_tokens(amount)
+        self.burn_lp_tokens(amount)?;
+
+        self.mint_lp.reload()?;
+        emit!(LiquidityRemoved {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_amount: amount,
+            lp_supply: self.mint_lp.supply,
+        });
+        Ok(())
     }
 
     pub fn withdraw_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
@@ -119,22 +174,25 @@ impl<'info> Withdraw<'info> {
         //     &binding_seed.as_ref(),
         //     &[self.config.bump],
         // ];
-        let seeds = &[&b"auth"[..], &[self.config.bump_auth]];
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.bump_auth]];
 
         let signer_seeds = &[&seeds[..]];
 
-        let (mint, decimals, vault, ata) = match is_x {
+        let (mint, decimals, vault, ata, token_program) = match is_x {
             true => (
                 self.mint_x.to_account_info(),
                 self.mint_x.decimals,
                 self.vault_x.to_account_info(),
                 self.user_ata_x.to_account_info(),
+                self.token_program_x.to_account_info(),
             ),
             false => (
                 self.mint_y.to_account_info(),
                 self.mint_y.decimals,
                 self.vault_y.to_account_info(),
                 self.user_ata_y.to_account_info(),
+                self.token_program_y.to_account_info(),
             ),
         };
 
@@ -145,11 +203,7 @@ impl<'info> Withdraw<'info> {
             mint,
         };
 
-        let ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            accounts,
-            signer_seeds,
-        );
+        let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
 
         transfer_checked(ctx, amount, decimals)?;
 
@@ -163,7 +217,7 @@ impl<'info> Withdraw<'info> {
             authority: self.user.to_account_info(),
         };
 
-        let ctx = CpiContext::new(self.token_program.to_account_info(), accounts);
+        let ctx = CpiContext::new(self.token_program_lp.to_account_info(), accounts);
 
         burn(ctx, amount)
     }
@@ -1,5 +1,5 @@
 use anchor_lang::error_code;
-use constant_product_curve::CurveError;
+use fairswap_math::MathError;
 
 #[error_code]
 pub enum AmmError {
@@ -38,18 +38,96 @@ pub enum AmmError {
 
     #[msg("Invalid input mint token")]
     InvalidInputMint,
+
+    #[msg("Invalid slot window")]
+    InvalidWindowSlots,
+
+    #[msg("Invalid surplus recipient")]
+    InvalidSurplusRecipient,
+
+    #[msg("Pending surplus must be collected first")]
+    PendingSurplus,
+
+    #[msg("Invalid route accounts")]
+    InvalidRoute,
+
+    #[msg("Invalid treasury")]
+    InvalidTreasury,
+
+    #[msg("No update authority set")]
+    NoAuthoritySet,
+
+    #[msg("Initial liquidity is below the minimum")]
+    InsufficientLiquidity,
+
+    #[msg("Deadline has passed")]
+    Expired,
+
+    #[msg("Pool only accepts batch orders")]
+    BatchAuctionOnly,
+
+    #[msg("Pool is not in batch-auction mode")]
+    BatchAuctionDisabled,
+
+    #[msg("Batch slot is in the past or too far ahead")]
+    InvalidBatchSlot,
+
+    #[msg("Batch is still accepting orders")]
+    BatchOpen,
+
+    #[msg("Batch is already cleared")]
+    BatchCleared,
+
+    #[msg("Batch is not cleared yet")]
+    BatchNotCleared,
+
+    #[msg("Revealed parameters do not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Commitment can't be revealed in this slot")]
+    RevealNotAllowed,
+
+    #[msg("Commitment can still be revealed")]
+    CommitmentNotExpired,
+
+    #[msg("Pool price has not reached the order's limit")]
+    LimitNotReached,
+
+    #[msg("Invalid amplification")]
+    InvalidAmp,
+
+    #[msg("Curve did not converge")]
+    CurveNotConverged,
+
+    #[msg("Not supported by the pool's curve")]
+    UnsupportedCurve,
+
+    #[msg("Amplification ramp is too fast or too short")]
+    InvalidAmpRamp,
+
+    #[msg("Invalid weights")]
+    InvalidWeights,
+
+    #[msg("Swap moves too large a share of a reserve")]
+    TradeTooLarge,
+
+    #[msg("Protocol fees must be collected first")]
+    ProtocolFeesPending,
 }
 
-impl From<CurveError> for AmmError {
-    fn from(error: CurveError) -> AmmError {
+impl From<MathError> for AmmError {
+    fn from(error: MathError) -> AmmError {
         match error {
-            CurveError::InvalidPrecision => AmmError::InvalidPrecision,
-            CurveError::Overflow => AmmError::Overflow,
-            CurveError::Underflow => AmmError::Underflow,
-            CurveError::InvalidFeeAmount => AmmError::InvalidFee,
-            CurveError::InsufficientBalance => AmmError::InsufficientBalance,
-            CurveError::ZeroBalance => AmmError::ZeroBalance,
-            CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded,
+            MathError::InvalidPrecision => AmmError::InvalidPrecision,
+            MathError::InvalidFee => AmmError::InvalidFee,
+            MathError::Overflow => AmmError::Overflow,
+            MathError::Underflow => AmmError::Underflow,
+            MathError::ZeroBalance => AmmError::ZeroBalance,
+            MathError::InsufficientBalance => AmmError::InsufficientBalance,
+            MathError::InvalidAmp => AmmError::InvalidAmp,
+            MathError::NotConverged => AmmError::CurveNotConverged,
+            MathError::InvalidWeights => AmmError::InvalidWeights,
+            MathError::TradeTooLarge => AmmError::TradeTooLarge,
         }
     }
 }
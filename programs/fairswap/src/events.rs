@@ -0,0 +1,240 @@
+use anchor_lang::prelude::*;
+
+use crate::states::{CurveType, SurplusPolicy};
+
+#[event]
+pub struct PoolInitialized {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub fee: u16,
+    pub protocol_fee_bps: u16,
+    pub window_slots: u64,
+    pub treasury: Pubkey,
+    pub surplus_policy: SurplusPolicy,
+    pub curve: CurveType,
+    pub amp: u64,
+    pub weight_x: u8,
+    pub weight_y: u8,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    // amounts sent by the user, before any transfer fee
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_amount: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    // amounts sent out of the vaults, before any transfer fee
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_amount: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct Swapped {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    pub amount_in: u64,
+    // output of the pool's curve before the fairness clamp
+    pub amount_out_raw: u64,
+    // output after the fairness clamp, sent out of the vault
+    pub amount_out: u64,
+    pub ratio: u128,
+    pub buying_x_high: Option<u128>,
+    pub buying_y_high: Option<u128>,
+    pub window_start_slot: u64,
+}
+
+#[event]
+pub struct PoolLocked {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct PoolUnlocked {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct WindowSlotsUpdated {
+    pub config: Pubkey,
+    pub old_window_slots: u64,
+    pub new_window_slots: u64,
+}
+
+#[event]
+pub struct SurplusPolicyUpdated {
+    pub config: Pubkey,
+    pub old_surplus_policy: SurplusPolicy,
+    pub new_surplus_policy: SurplusPolicy,
+}
+
+#[event]
+pub struct SurplusCollected {
+    pub config: Pubkey,
+    pub recipient: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub config: Pubkey,
+    pub treasury: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub config: Pubkey,
+    pub old_fee: u16,
+    pub new_fee: u16,
+    pub old_protocol_fee_bps: u16,
+    pub new_protocol_fee_bps: u16,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityAccepted {
+    pub config: Pubkey,
+    pub old_authority: Option<Pubkey>,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityRenounced {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct BatchAuctionUpdated {
+    pub config: Pubkey,
+    pub batch_auction: bool,
+}
+
+#[event]
+pub struct AmpRampUpdated {
+    pub config: Pubkey,
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct BatchOrderPlaced {
+    pub config: Pubkey,
+    pub batch: Pubkey,
+    pub user: Pubkey,
+    pub slot: u64,
+    pub mint_in: Pubkey,
+    // sent by the user, before any transfer fee
+    pub amount_in: u64,
+    // counted towards the batch after transfer and swap fees
+    pub amount_in_net: u64,
+    pub amount_out_min: u64,
+}
+
+#[event]
+pub struct BatchCleared {
+    pub config: Pubkey,
+    pub batch: Pubkey,
+    pub slot: u64,
+    pub amount_in_x: u64,
+    pub amount_in_y: u64,
+    pub amount_out_x: u64,
+    pub amount_out_y: u64,
+    // clearing price, Y per X scaled by 10^precision
+    pub ratio: u128,
+}
+
+#[event]
+pub struct BatchOrderClaimed {
+    pub config: Pubkey,
+    pub batch: Pubkey,
+    pub user: Pubkey,
+    // sent out of the vaults, before any transfer fee
+    pub amount_x: u64,
+    pub amount_y: u64,
+    // inputs handed back because the side's share missed its minimum, part of the above
+    pub refund_x: u64,
+    pub refund_y: u64,
+}
+
+#[event]
+pub struct SwapCommitted {
+    pub config: Pubkey,
+    pub commitment: Pubkey,
+    pub user: Pubkey,
+    pub hash: [u8; 32],
+    // escrowed net of transfer fees
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct CommitmentRefunded {
+    pub config: Pubkey,
+    pub commitment: Pubkey,
+    pub user: Pubkey,
+    // sent out of the vaults, before any transfer fee
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct LimitOrderPlaced {
+    pub config: Pubkey,
+    pub order: Pubkey,
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    // escrowed net of transfer fees
+    pub amount_in: u64,
+    pub min_ratio: u128,
+    pub keeper_reward: u64,
+}
+
+#[event]
+pub struct LimitOrderFilled {
+    pub config: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub amount_in: u64,
+    // sent out of the vault, before any transfer fee
+    pub amount_out: u64,
+    // output per unit of input the order was filled at
+    pub ratio: u128,
+    pub keeper_reward: u64,
+}
+
+#[event]
+pub struct LimitOrderCancelled {
+    pub config: Pubkey,
+    pub order: Pubkey,
+    pub user: Pubkey,
+    // sent out of the vault, before any transfer fee
+    pub amount_in: u64,
+}
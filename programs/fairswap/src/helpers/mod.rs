@@ -1,5 +1,10 @@
 use crate::errors::AmmError;
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{self, spl_token_2022::extension::transfer_fee::TransferFeeConfig},
+    token_interface::get_mint_extension_data,
+};
+use fairswap_math::{fairness, Curve};
 
 #[macro_export]
 macro_rules! assert_non_zero {
@@ -12,12 +17,21 @@ macro_rules! assert_non_zero {
 
 #[macro_export]
 macro_rules! assert_has_authority {
-    ($x:expr) => {
-        match $x.config.authority {
+    ($config:expr, $signer:expr) => {
+        match $config.authority {
             Some(authority) => {
-                require_keys_eq!(authority, $x.user.key)(, AmmError::InvalidAuthority)
-            },
-            None => return err!(AmmError::Unauthorized)
+                require_keys_eq!(authority, $signer, AmmError::Unauthorized)
+            }
+            None => return err!(AmmError::NoAuthoritySet),
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! assert_not_expired {
+    ($deadline:expr) => {
+        if $deadline.is_expired(&Clock::get()?) {
+            return err!(AmmError::Expired);
         }
     };
 }
@@ -31,15 +45,55 @@ macro_rules! assert_not_locked {
     };
 }
 
+pub use fairswap_math::fairness::lp_decimals;
+
+// The math lives in fairswap-math, these wrap it in the program's error type
+
 pub fn calculate_limit_price(amount1: u64, amount2: u64, precision: u8) -> Result<u128> {
-    let result = (amount1 as u128)
-        .checked_mul(
-            10u128
-                .checked_pow(precision as u32)
-                .ok_or(AmmError::InvalidPrecision)?,
-        )
-        .ok_or(AmmError::Overflow)?
-        .checked_div(amount2 as u128)
-        .ok_or(AmmError::Overflow)?;
-    Ok(result)
+    Ok(fairness::limit_price(amount1, amount2, precision).map_err(AmmError::from)?)
+}
+
+// Input for which the pool's curve releases amount_out, fee included
+pub fn calculate_amount_in(
+    curve: Curve,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<u64> {
+    Ok(curve
+        .swap_amount_in(reserve_in, reserve_out, amount_out, fee)
+        .map_err(AmmError::from)?)
+}
+
+// Input needed to receive amount_out at a ratio from calculate_limit_price, rounded up
+pub fn calculate_amount_at_ratio(amount_out: u64, ratio: u128, precision: u8) -> Result<u64> {
+    Ok(fairness::amount_in_at_ratio(amount_out, ratio, precision).map_err(AmmError::from)?)
+}
+
+// Fee a Token-2022 TransferFee mint withholds when amount is sent, 0 for any other mint
+pub fn get_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    match transfer_fee_config(mint) {
+        Some(config) => Ok(config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(AmmError::Overflow)?),
+        None => Ok(0),
+    }
+}
+
+// Fee to add on top of amount so that amount arrives after the transfer
+pub fn get_transfer_inverse_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    match transfer_fee_config(mint) {
+        Some(config) => Ok(config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(AmmError::Overflow)?),
+        None => Ok(0),
+    }
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Option<TransferFeeConfig> {
+    if *mint.owner != token_2022::ID {
+        return None;
+    }
+    get_mint_extension_data::<TransferFeeConfig>(mint).ok()
 }
@@ -6,27 +6,66 @@ pub mod contexts;
 pub use contexts::*;
 
 pub mod errors;
+pub mod events;
 pub mod helpers;
 pub mod states;
+use states::{CurveType, Deadline, SurplusPolicy};
 
 #[program]
 pub mod fairswap {
     use super::*;
 
     // Intialize the pool
-    pub fn initialize(ctx: Context<Initialize>, seed: u64, fee: u16) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        seed: u64,
+        fee: u16,
+        protocol_fee_bps: u16,
+        window_slots: u64,
+        treasury: Pubkey,
+        surplus_policy: SurplusPolicy,
+        curve: CurveType,
+        amp: u64,
+        weight_x: u8,
+        weight_y: u8,
+    ) -> Result<()> {
         // save config
-        ctx.accounts.save_config(seed, fee, &ctx.bumps)
+        ctx.accounts.save_config(
+            seed,
+            fee,
+            protocol_fee_bps,
+            window_slots,
+            treasury,
+            surplus_policy,
+            curve,
+            amp,
+            weight_x,
+            weight_y,
+            &ctx.bumps,
+        )
     }
 
     // Add liquidity to receive LP tokens
-    pub fn deposit(ctx: Context<Deposit>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
-        ctx.accounts.deposit(amount, max_x, max_y)
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
+        ctx.accounts.deposit(amount, max_x, max_y, deadline)
     }
 
     // Burn LP tokens to withdraw tokens
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64, min_x: u64, min_y: u64) -> Result<()> {
-        ctx.accounts.withdraw(amount, min_x, min_y)
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(amount, min_x, min_y, deadline)
     }
 
     pub fn swap(
@@ -34,8 +73,138 @@ pub mod fairswap {
         mint_deposit: Pubkey,
         amount_in: u64,
         amount_out_min: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
+        ctx.accounts
+            .swap(mint_deposit, amount_in, amount_out_min, deadline)
+    }
+
+    // Receive exactly amount_out, paying at most max_amount_in at the fair ratio
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        mint_deposit: Pubkey,
+        amount_out: u64,
+        max_amount_in: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
+        ctx.accounts
+            .swap_exact_out(mint_deposit, amount_out, max_amount_in, deadline)
+    }
+
+    // Swap through several pools passed as remaining accounts, checking slippage once at the end
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        mint_in: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        deadline: Deadline,
+    ) -> Result<()> {
+        ctx.accounts.route_swap(
+            ctx.remaining_accounts,
+            mint_in,
+            amount_in,
+            amount_out_min,
+            deadline,
+        )
+    }
+
+    // Simulate a swap without changing state, the result is returned as return data
+    pub fn quote(
+        ctx: Context<Quote>,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+    ) -> Result<SwapQuote> {
+        ctx.accounts.quote(mint_deposit, amount_in, amount_out_min)
+    }
+
+    // Queue a swap into the batch for `slot`, batch-auction pools only
+    pub fn place_batch_order(
+        ctx: Context<PlaceBatchOrder>,
+        slot: u64,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .place_batch_order(slot, mint_deposit, amount_in, amount_out_min, &ctx.bumps)
+    }
+
+    // Clear a batch whose slot has passed at a single price, callable by anyone
+    pub fn clear_batch(ctx: Context<ClearBatch>) -> Result<()> {
+        ctx.accounts.clear_batch()
+    }
+
+    // Receive an order's share of its cleared batch
+    pub fn claim_batch_order(ctx: Context<ClaimBatchOrder>) -> Result<()> {
+        ctx.accounts.claim_batch_order()
+    }
+
+    // Escrow tokens behind the hash of a swap, hiding its direction and size until revealed
+    pub fn commit_swap(
+        ctx: Context<CommitSwap>,
+        hash: [u8; 32],
+        amount_x: u64,
+        amount_y: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .commit_swap(hash, amount_x, amount_y, &ctx.bumps)
+    }
+
+    // Execute a committed swap once its parameters and salt are revealed
+    pub fn reveal_swap(
+        ctx: Context<RevealSwap>,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts
+            .reveal_swap(mint_deposit, amount_in, amount_out_min, salt)
+    }
+
+    // Take back the escrow of a commitment whose reveal window has passed
+    pub fn refund_commitment(ctx: Context<RefundCommitment>) -> Result<()> {
+        ctx.accounts.refund_commitment()
+    }
+
+    // Rest an order selling amount_in once the pool pays min_ratio output per unit of input
+    pub fn place_limit_order(
+        ctx: Context<PlaceLimitOrder>,
+        id: u64,
+        mint_deposit: Pubkey,
+        amount_in: u64,
+        min_ratio: u128,
+        keeper_reward: u64,
     ) -> Result<()> {
-        ctx.accounts.swap(mint_deposit, amount_in, amount_out_min)
+        ctx.accounts.place_limit_order(
+            id,
+            mint_deposit,
+            amount_in,
+            min_ratio,
+            keeper_reward,
+            &ctx.bumps,
+        )
+    }
+
+    // Fill a limit order whose price has been reached, callable by anyone for the reward
+    pub fn fill_limit_order(ctx: Context<FillLimitOrder>) -> Result<()> {
+        ctx.accounts.fill_limit_order()
+    }
+
+    // Close a limit order and take back its escrow
+    pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+        ctx.accounts.cancel_limit_order()
+    }
+
+    // Pay out the fairness surplus set aside for the treasury or beneficiary
+    pub fn collect_surplus(ctx: Context<CollectSurplus>) -> Result<()> {
+        ctx.accounts.collect_surplus()
+    }
+
+    // Send the accrued protocol share of swap fees to the treasury
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        ctx.accounts.collect_protocol_fees()
     }
 
     pub fn lock(ctx: Context<Update>) -> Result<()> {
@@ -45,4 +214,49 @@ pub mod fairswap {
     pub fn unlock(ctx: Context<Update>) -> Result<()> {
         ctx.accounts.unlock()
     }
+
+    // Change how many slots the fairness high-water marks persist for
+    pub fn set_window_slots(ctx: Context<Update>, window_slots: u64) -> Result<()> {
+        ctx.accounts.set_window_slots(window_slots)
+    }
+
+    // Change where the fairness surplus goes
+    pub fn set_surplus_policy(ctx: Context<Update>, surplus_policy: SurplusPolicy) -> Result<()> {
+        ctx.accounts.set_surplus_policy(surplus_policy)
+    }
+
+    // Switch between direct swaps and per-slot batch auctions
+    pub fn set_batch_auction(ctx: Context<Update>, batch_auction: bool) -> Result<()> {
+        ctx.accounts.set_batch_auction(batch_auction)
+    }
+
+    // Move a StableSwap pool's amplification linearly to target_amp by end_ts
+    pub fn ramp_amp(ctx: Context<Update>, target_amp: u64, end_ts: i64) -> Result<()> {
+        ctx.accounts.ramp_amp(target_amp, end_ts)
+    }
+
+    // Hold the amplification where an ongoing ramp has got to
+    pub fn stop_ramp_amp(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.stop_ramp_amp()
+    }
+
+    // Change the swap fee and the protocol's share of it
+    pub fn set_fee(ctx: Context<Update>, fee: u16, protocol_fee_bps: u16) -> Result<()> {
+        ctx.accounts.set_fee(fee, protocol_fee_bps)
+    }
+
+    // First step of an authority transfer, the new authority still has to accept
+    pub fn propose_authority(ctx: Context<Update>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.propose_authority(new_authority)
+    }
+
+    // Second step of an authority transfer, signed by the proposed authority
+    pub fn accept_authority(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.accept_authority()
+    }
+
+    // Drop the authority for good, freezing the pool's settings
+    pub fn renounce_authority(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.renounce_authority()
+    }
 }
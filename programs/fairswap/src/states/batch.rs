@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+// Swaps queued for one slot of a batch-auction pool. Orders are accepted up to and
+// including `slot`, after which a crank clears the whole batch at a single price.
+#[account]
+#[derive(InitSpace)]
+pub struct Batch {
+    pub config: Pubkey,
+    pub slot: u64,
+    // pays the rent and gets it back once the last order is claimed
+    pub payer: Pubkey,
+    // escrowed inputs net of transfer and swap fees, per token
+    pub amount_in_x: u64,
+    pub amount_in_y: u64,
+    // swap fees charged on the inputs, added to the reserves when the batch clears
+    pub fee_x: u64,
+    pub fee_y: u64,
+    // totals owed at the clearing price, X to the Y side and Y to the X side
+    pub amount_out_x: u64,
+    pub amount_out_y: u64,
+    // settled by claims so far, paid out or returned to the reserves
+    pub claimed_x: u64,
+    pub claimed_y: u64,
+    pub cleared: bool,
+    // orders not yet claimed
+    pub open_orders: u32,
+    pub bump: u8,
+}
+
+impl Batch {
+    // ~1 minute, orders can't park tokens in batches that clear far in the future
+    pub const MAX_SLOTS_AHEAD: u64 = 150;
+
+    pub fn is_closed(&self, current_slot: u64) -> bool {
+        current_slot > self.slot
+    }
+}
+
+// One user's inputs to a batch, either side or both
+#[account]
+#[derive(InitSpace)]
+pub struct BatchOrder {
+    pub batch: Pubkey,
+    pub owner: Pubkey,
+    // net of transfer and swap fees, as counted in the batch totals
+    pub amount_in_x: u64,
+    pub amount_in_y: u64,
+    // least X paid for the Y side and least Y paid for the X side, below it the side's
+    // input is refunded on claim
+    pub min_out_x: u64,
+    pub min_out_y: u64,
+    pub bump: u8,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+// A swap whose parameters stay hidden behind a hash until it is revealed. The input is
+// escrowed up front; escrowing on both sides also hides which way the swap goes.
+#[account]
+#[derive(InitSpace)]
+pub struct SwapCommitment {
+    pub config: Pubkey,
+    pub owner: Pubkey,
+    // hash of the swap parameters and a secret salt, see `SwapCommitment::hash`
+    pub hash: [u8; 32],
+    // escrowed in the vaults net of transfer fees, the revealed input comes out of these
+    pub amount_x: u64,
+    pub amount_y: u64,
+    // slot the commitment was made in
+    pub slot: u64,
+    pub bump: u8,
+}
+
+impl SwapCommitment {
+    // a reveal in the commit slot or the next one could still be bundled with the commit
+    pub const MIN_REVEAL_SLOTS: u64 = 2;
+    // ~1 minute to reveal, after that the escrow can only be refunded
+    pub const MAX_REVEAL_SLOTS: u64 = 150;
+
+    pub fn hash(
+        mint_deposit: &Pubkey,
+        amount_in: u64,
+        amount_out_min: u64,
+        salt: &[u8; 32],
+    ) -> [u8; 32] {
+        hashv(&[
+            mint_deposit.as_ref(),
+            &amount_in.to_le_bytes(),
+            &amount_out_min.to_le_bytes(),
+            salt,
+        ])
+        .to_bytes()
+    }
+
+    pub fn can_reveal(&self, current_slot: u64) -> bool {
+        current_slot >= self.slot.saturating_add(Self::MIN_REVEAL_SLOTS)
+            && !self.is_expired(current_slot)
+    }
+
+    pub fn is_expired(&self, current_slot: u64) -> bool {
+        current_slot > self.slot.saturating_add(Self::MAX_REVEAL_SLOTS)
+    }
+}
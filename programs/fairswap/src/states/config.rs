@@ -1,15 +1,135 @@
 use anchor_lang::prelude::*;
+use fairswap_math::{stable, weighted, Curve};
 
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
     pub seed: u64,
-    pub authority: Pubkey,
+    // None once renounced, the pool's settings are then frozen
+    pub authority: Option<Pubkey>,
+    // proposed authority that still has to accept the transfer
+    pub pending_authority: Option<Pubkey>,
     pub fee: u16,
+    // share of `fee` kept for the treasury instead of the LPs, in basis points of the fee
+    pub protocol_fee_bps: u16,
     pub mint_x: Pubkey,
     pub mint_y: Pubkey,
     pub locked: bool,
+    pub treasury: Pubkey,
+    pub surplus_policy: SurplusPolicy,
+    // swaps are queued per slot and cleared at one price instead of executing directly
+    pub batch_auction: bool,
+    // invariant the pool trades on, fixed at initialization
+    pub curve: CurveType,
+    // StableSwap amplification, unused by the other curves
+    pub amp: AmpRamp,
+    // percent of the pool's value in each token, 50 / 50 unless the curve is weighted
+    pub weight_x: u8,
+    pub weight_y: u8,
     // pub bump_lp: u8,
     pub bump: u8,
     pub bump_auth: u8,
 }
+
+impl Config {
+    // a ramp can't finish sooner than a day after it starts
+    pub const MIN_RAMP_DURATION: i64 = 86_400;
+    // nor move amp by more than this factor either way
+    pub const MAX_AMP_CHANGE: u64 = 10;
+
+    // only StableSwap pools carry an amplification
+    pub fn is_valid_amp(curve: CurveType, amp: u64) -> bool {
+        match curve {
+            CurveType::StableSwap => amp > 0 && amp <= stable::MAX_AMP,
+            _ => amp == 0,
+        }
+    }
+
+    // only weighted pools move away from 50 / 50
+    pub fn is_valid_weights(curve: CurveType, weight_x: u8, weight_y: u8) -> bool {
+        match curve {
+            CurveType::Weighted => weighted::is_valid_weights(weight_x, weight_y),
+            _ => weight_x == 50 && weight_y == 50,
+        }
+    }
+
+    // The curve swaps trade on at `timestamp`, part way through any amp ramp. Weights are
+    // oriented for selling X, `reversed()` gives the curve for selling Y.
+    pub fn curve(&self, timestamp: i64) -> Curve {
+        match self.curve {
+            CurveType::ConstantProduct => Curve::ConstantProduct,
+            CurveType::StableSwap => Curve::StableSwap {
+                amp: self.amp.current(timestamp),
+            },
+            CurveType::Weighted => Curve::Weighted {
+                weight_in: self.weight_x,
+                weight_out: self.weight_y,
+            },
+        }
+    }
+
+    // owner of the accounts fairness surplus is paid out to, None if it stays with LPs
+    pub fn surplus_recipient(&self) -> Option<Pubkey> {
+        match self.surplus_policy {
+            SurplusPolicy::Lp => None,
+            SurplusPolicy::Treasury => Some(self.treasury),
+            SurplusPolicy::Beneficiary(beneficiary) => Some(beneficiary),
+        }
+    }
+}
+
+// What happens to the output withheld from traders by the high-water-mark clamp
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SurplusPolicy {
+    // stays in the vaults and accrues to LPs
+    Lp,
+    // set aside for the protocol treasury
+    Treasury,
+    // set aside for a configurable beneficiary
+    Beneficiary(Pubkey),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CurveType {
+    // x * y = k
+    ConstantProduct,
+    // Curve-style amplified invariant for pairs that trade near 1:1
+    StableSwap,
+    // x^weight_x * y^weight_y = k, Balancer-style
+    Weighted,
+}
+
+// Amplification moving linearly from initial_amp at start_ts to target_amp at end_ts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct AmpRamp {
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+impl AmpRamp {
+    // a pool that isn't ramping holds target_amp
+    pub fn fixed(amp: u64) -> Self {
+        Self {
+            initial_amp: amp,
+            target_amp: amp,
+            start_ts: 0,
+            end_ts: 0,
+        }
+    }
+
+    pub fn current(&self, timestamp: i64) -> u64 {
+        if timestamp >= self.end_ts {
+            return self.target_amp;
+        }
+        if timestamp <= self.start_ts {
+            return self.initial_amp;
+        }
+        // both amps are at most stable::MAX_AMP, so the i128 math can't overflow
+        let elapsed = (timestamp - self.start_ts) as i128;
+        let duration = (self.end_ts - self.start_ts) as i128;
+        let change = self.target_amp as i128 - self.initial_amp as i128;
+        (self.initial_amp as i128 + change * elapsed / duration) as u64
+    }
+}
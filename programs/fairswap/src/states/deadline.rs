@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+// Latest point a user instruction may still execute at
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Deadline {
+    // unix timestamp in seconds, inclusive
+    Timestamp(i64),
+    // slot, inclusive
+    Slot(u64),
+}
+
+impl Deadline {
+    pub fn is_expired(&self, clock: &Clock) -> bool {
+        match *self {
+            Deadline::Timestamp(timestamp) => clock.unix_timestamp > timestamp,
+            Deadline::Slot(slot) => clock.slot > slot,
+        }
+    }
+}
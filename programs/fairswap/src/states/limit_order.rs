@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+// A resting order selling its escrowed input once the pool pays at least `min_ratio`.
+// Anyone can fill it and collect the keeper reward.
+#[account]
+#[derive(InitSpace)]
+pub struct LimitOrder {
+    pub config: Pubkey,
+    pub owner: Pubkey,
+    // picked by the owner so they can keep several orders open on one pool
+    pub id: u64,
+    pub mint_in: Pubkey,
+    // escrowed in the vaults net of transfer fees, sold in full by the fill
+    pub amount_in: u64,
+    // minimum output per unit of input as returned by calculate_limit_price(out, in, precision)
+    pub min_ratio: u128,
+    // lamports held in the order account on top of its rent, paid to whoever fills it
+    pub keeper_reward: u64,
+    pub bump: u8,
+}
@@ -3,3 +3,15 @@ pub use config::*;
 
 pub mod pooldata;
 pub use pooldata::*;
+
+pub mod deadline;
+pub use deadline::*;
+
+pub mod batch;
+pub use batch::*;
+
+pub mod commitment;
+pub use commitment::*;
+
+pub mod limit_order;
+pub use limit_order::*;
@@ -1,11 +1,321 @@
 use anchor_lang::prelude::*;
+use fairswap_math::{fairness, Curve};
+
+use crate::errors::AmmError;
+use crate::helpers::calculate_limit_price;
+use crate::states::SurplusPolicy;
 
 #[account]
 #[derive(InitSpace)]
 pub struct PoolData {
+    // first slot of the current fairness window
     pub last_slot: u64,
+    // number of slots the high-water marks are kept for
+    pub window_slots: u64,
     pub buying_x_high: Option<u128>,
     pub buying_y_high: Option<u128>,
+    // cumulative output withheld by the fairness clamp, per token
+    pub surplus_x: u128,
+    pub surplus_y: u128,
+    // surplus held in the vaults on behalf of the surplus recipient
+    pub pending_surplus_x: u64,
+    pub pending_surplus_y: u64,
+    // protocol's share of swap fees held in the vaults until collected
+    pub protocol_fees_x: u64,
+    pub protocol_fees_y: u64,
+    // held in the vaults for batch orders, escrowed inputs or payouts not yet claimed
+    pub batch_x: u64,
+    pub batch_y: u64,
+    // escrowed in the vaults for swap commitments not yet revealed or refunded
+    pub committed_x: u64,
+    pub committed_y: u64,
+    // escrowed in the vaults for resting limit orders
+    pub limit_orders_x: u64,
+    pub limit_orders_y: u64,
+    // time-weighted sums of the spot prices, scaled by 10^precision and wrapping on overflow
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+    pub oracle_timestamp: i64,
+    // ring buffer of accumulator snapshots, observation_index points at the newest one
+    pub observations: [Observation; PoolData::OBSERVATIONS],
+    pub observation_index: u8,
     pub bump: u8,
     pub precision: u8,
 }
+
+impl PoolData {
+    // ~1 minute of slots, longer windows leave the pool stale against external markets
+    pub const MAX_WINDOW_SLOTS: u64 = 150;
+    pub const OBSERVATIONS: usize = 16;
+    // ratios are u64 amounts scaled by 10^precision in a u128, 18 keeps them clear of overflow
+    pub const MAX_PRECISION: u8 = 18;
+    // minimum seconds between two observations, 16 of them cover at least 16 minutes
+    pub const OBSERVATION_PERIOD: i64 = 60;
+    // LP locked forever on the first deposit so the supply can never be drained to dust
+    pub const MINIMUM_LIQUIDITY: u64 = 1000;
+
+    pub fn is_valid_window(window_slots: u64) -> bool {
+        window_slots > 0 && window_slots <= Self::MAX_WINDOW_SLOTS
+    }
+
+    // Ratios compare raw amounts, so a pair with different decimals needs extra digits
+    // to keep 6 significant ones on the side with the smaller raw amounts.
+    pub fn precision_for(decimals_x: u8, decimals_y: u8) -> Result<u8> {
+        let precision = 6 + decimals_x.abs_diff(decimals_y);
+        require!(precision <= Self::MAX_PRECISION, AmmError::InvalidPrecision);
+        Ok(precision)
+    }
+
+    // a new window starts once `window_slots` slots have passed since `last_slot`
+    pub fn is_window_expired(&self, current_slot: u64) -> bool {
+        current_slot >= self.last_slot.saturating_add(self.window_slots)
+    }
+
+    // vault balances minus the tokens that are owed to someone other than the LPs
+    pub fn reserves(&self, vault_x: u64, vault_y: u64) -> Result<(u64, u64)> {
+        let x = vault_x
+            .checked_sub(self.pending_surplus_x)
+            .and_then(|x| x.checked_sub(self.protocol_fees_x))
+            .and_then(|x| x.checked_sub(self.batch_x))
+            .and_then(|x| x.checked_sub(self.committed_x))
+            .and_then(|x| x.checked_sub(self.limit_orders_x))
+            .ok_or(AmmError::Underflow)?;
+        let y = vault_y
+            .checked_sub(self.pending_surplus_y)
+            .and_then(|y| y.checked_sub(self.protocol_fees_y))
+            .and_then(|y| y.checked_sub(self.batch_y))
+            .and_then(|y| y.checked_sub(self.committed_y))
+            .and_then(|y| y.checked_sub(self.limit_orders_y))
+            .ok_or(AmmError::Underflow)?;
+        Ok((x, y))
+    }
+
+    pub fn capture_surplus(
+        &mut self,
+        is_x: bool,
+        amount: u64,
+        policy: SurplusPolicy,
+    ) -> Result<()> {
+        let (surplus, pending) = match is_x {
+            true => (&mut self.surplus_x, &mut self.pending_surplus_x),
+            false => (&mut self.surplus_y, &mut self.pending_surplus_y),
+        };
+        *surplus = surplus
+            .checked_add(amount as u128)
+            .ok_or(AmmError::Overflow)?;
+        if policy != SurplusPolicy::Lp {
+            *pending = pending.checked_add(amount).ok_or(AmmError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    // Opens a window at the pool's first deposit, so the first swap is already measured
+    // against the deposited price instead of setting the baseline itself.
+    pub fn seed_window(&mut self, x: u64, y: u64, curve: Curve, current_slot: u64) -> Result<()> {
+        let (spot_x, spot_y) = self.spot_ratios(x, y, curve)?;
+        self.last_slot = current_slot;
+        self.buying_x_high = Some(spot_x);
+        self.buying_y_high = Some(spot_y);
+        Ok(())
+    }
+
+    // Marginal prices of X in Y and of Y in X on the pool's curve, the ratios a swap of
+    // next to nothing would execute at. `curve` is oriented for selling X.
+    pub fn spot_ratios(&self, x: u64, y: u64, curve: Curve) -> Result<(u128, u128)> {
+        let spot_x = curve
+            .reversed()
+            .spot_ratio(y, x, self.precision)
+            .map_err(AmmError::from)?;
+        let spot_y = curve
+            .spot_ratio(x, y, self.precision)
+            .map_err(AmmError::from)?;
+        Ok((spot_x, spot_y))
+    }
+
+    // A proportional deposit or withdrawal leaves the spot price alone but changes depth,
+    // so the price impact baked into each high-water mark is scaled by old/new supply.
+    pub fn rescale_window(
+        &mut self,
+        reserves: (u64, u64),
+        curve: Curve,
+        old_supply: u64,
+        new_supply: u64,
+        current_slot: u64,
+    ) -> Result<()> {
+        if self.is_window_expired(current_slot) {
+            return Ok(());
+        }
+        if new_supply == 0 || reserves.0 == 0 || reserves.1 == 0 {
+            self.buying_x_high = None;
+            self.buying_y_high = None;
+            return Ok(());
+        }
+
+        let (spot_x, spot_y) = self.spot_ratios(reserves.0, reserves.1, curve)?;
+        let rescale = |high: Option<u128>, spot: u128| -> Result<Option<u128>> {
+            match high {
+                Some(high) => Ok(Some(
+                    fairness::rescale_high(high, spot, old_supply, new_supply)
+                        .map_err(AmmError::from)?,
+                )),
+                None => Ok(None),
+            }
+        };
+        self.buying_x_high = rescale(self.buying_x_high, spot_x)?;
+        self.buying_y_high = rescale(self.buying_y_high, spot_y)?;
+        Ok(())
+    }
+
+    // Sets aside protocol_fee_bps of a swap fee paid in X (is_x) or Y for the treasury
+    pub fn accrue_protocol_fee(
+        &mut self,
+        is_x: bool,
+        fee: u64,
+        protocol_fee_bps: u16,
+    ) -> Result<()> {
+        let amount = (fee as u128)
+            .checked_mul(protocol_fee_bps as u128)
+            .ok_or(AmmError::Overflow)?
+            / 10_000;
+        let fees = match is_x {
+            true => &mut self.protocol_fees_x,
+            false => &mut self.protocol_fees_y,
+        };
+        *fees = fees.checked_add(amount as u64).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    // Accumulates the price that held since the last update, must run before reserves change.
+    pub fn update_oracle(
+        &mut self,
+        reserve_x: u64,
+        reserve_y: u64,
+        curve: Curve,
+        timestamp: i64,
+    ) -> Result<()> {
+        let elapsed = timestamp.saturating_sub(self.oracle_timestamp);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+
+        if reserve_x > 0 && reserve_y > 0 {
+            let (price_x, price_y) = self.spot_ratios(reserve_x, reserve_y, curve)?;
+            self.price_x_cumulative = self
+                .price_x_cumulative
+                .wrapping_add(price_x.wrapping_mul(elapsed as u128));
+            self.price_y_cumulative = self
+                .price_y_cumulative
+                .wrapping_add(price_y.wrapping_mul(elapsed as u128));
+        }
+        self.oracle_timestamp = timestamp;
+
+        let latest = self.observations[self.observation_index as usize];
+        if timestamp.saturating_sub(latest.timestamp) >= Self::OBSERVATION_PERIOD {
+            self.observation_index =
+                ((self.observation_index as usize + 1) % Self::OBSERVATIONS) as u8;
+            self.observations[self.observation_index as usize] = Observation {
+                timestamp,
+                price_x_cumulative: self.price_x_cumulative,
+                price_y_cumulative: self.price_y_cumulative,
+            };
+        }
+        Ok(())
+    }
+
+    // Runs amount_in through the curve and clamps the execution ratio to the window's
+    // high-water mark, opening a new window first if the current one has expired.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fair_swap(
+        &mut self,
+        reserves: (u64, u64),
+        curve: Curve,
+        fee: u16,
+        is_buying_x: bool,
+        amount_in: u64,
+        amount_out_min: u64,
+        current_slot: u64,
+    ) -> Result<FairSwapResult> {
+        let (reserve_x, reserve_y) = reserves;
+        let (reserve_in, reserve_out, swap_curve) = match is_buying_x {
+            true => (reserve_y, reserve_x, curve.reversed()),
+            false => (reserve_x, reserve_y, curve),
+        };
+        let res = swap_curve
+            .swap_exact_in(reserve_in, reserve_out, amount_in, fee)
+            .map_err(AmmError::from)?;
+        require!(res.withdraw >= amount_out_min, AmmError::SlippageExceeded);
+
+        let current_ratio = calculate_limit_price(res.deposit, res.withdraw, self.precision)?;
+
+        if self.is_window_expired(current_slot) {
+            self.last_slot = current_slot;
+            let (spot_x, spot_y) = self.spot_ratios(reserve_x, reserve_y, curve)?;
+            if is_buying_x {
+                self.buying_x_high = Some(current_ratio);
+                self.buying_y_high = Some(spot_y);
+            } else {
+                self.buying_x_high = Some(spot_x);
+                self.buying_y_high = Some(current_ratio);
+            }
+        }
+
+        // Determine the price limit based on whether we are buying X or Y
+        let high = match is_buying_x {
+            true => &mut self.buying_x_high,
+            false => &mut self.buying_y_high,
+        };
+        let ratio = fairness::apply_high_water_mark(high, current_ratio);
+
+        // never pay out more than the curve itself would
+        let withdraw_amount = fairness::amount_out_at_ratio(res.deposit, ratio, self.precision)
+            .map_err(AmmError::from)?
+            .min(res.withdraw);
+
+        Ok(FairSwapResult {
+            deposit: res.deposit,
+            withdraw: withdraw_amount,
+            raw_withdraw: res.withdraw,
+            ratio,
+            fee: res.fee,
+        })
+    }
+}
+
+pub struct FairSwapResult {
+    pub deposit: u64,
+    // amount paid out at the fair ratio
+    pub withdraw: u64,
+    // amount the curve alone would have paid out
+    pub raw_withdraw: u64,
+    // execution ratio after the high-water-mark clamp
+    pub ratio: u128,
+    // curve fee charged on the deposit, in the deposited token
+    pub fee: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+}
+
+impl Observation {
+    // Average prices of X and Y between two observations, scaled by 10^precision
+    pub fn twap(older: &Observation, newer: &Observation) -> Option<(u128, u128)> {
+        let elapsed = newer.timestamp.checked_sub(older.timestamp)?;
+        if elapsed <= 0 {
+            return None;
+        }
+        Some((
+            newer
+                .price_x_cumulative
+                .wrapping_sub(older.price_x_cumulative)
+                / elapsed as u128,
+            newer
+                .price_y_cumulative
+                .wrapping_sub(older.price_y_cumulative)
+                / elapsed as u128,
+        ))
+    }
+}
@@ -8,7 +8,7 @@ use anchor_spl::{
         mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
     },
 };
-use constant_product_curve::ConstantProduct;
+use fairswap_math::curve;
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -84,17 +84,13 @@ impl<'info> Deposit<'info> {
             && self.vault_y.amount == 0
         {
             true => (max_x, max_y),
-            false => {
-                let amount = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount,
-                    self.vault_y.amount,
-                    self.mint_lp.supply,
-                    amount,
-                    6,
-                )
-                .map_err(AmmError::from)?;
-                (amount.x, amount.y)
-            }
+            false => curve::deposit_amounts(
+                self.vault_x.amount,
+                self.vault_y.amount,
+                self.mint_lp.supply,
+                amount,
+            )
+            .map_err(AmmError::from)?,
         };
 
         require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
@@ -6,7 +6,7 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
-use constant_product_curve::{ConstantProduct, LiquidityPair};
+use fairswap_math::curve;
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
@@ -78,21 +78,14 @@ impl<'info> Swap<'info> {
         assert_not_expired!(expiration);
         assert_non_zero!([amount]);
 
-        let mut curve = ConstantProduct::init(
-            self.vault_x.amount,
-            self.vault_y.amount,
-            self.mint_lp.supply,
-            self.config.fee,
-            None,
-        )
-        .map_err(AmmError::from)?;
-
-        let p = match is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
+        let (reserve_in, reserve_out) = match is_x {
+            true => (self.vault_x.amount, self.vault_y.amount),
+            false => (self.vault_y.amount, self.vault_x.amount),
         };
 
-        let res = curve.swap(p, amount, min).map_err(AmmError::from)?;
+        let res = curve::swap_exact_in(reserve_in, reserve_out, amount, self.config.fee)
+            .map_err(AmmError::from)?;
+        require!(res.withdraw >= min, AmmError::SlippageExceeded);
 
         assert_non_zero!([res.deposit, res.withdraw]);
 
@@ -126,9 +119,9 @@ impl<'info> Swap<'info> {
             to,
             authority: self.user.to_account_info(),
         };
-        
+
         let ctx = CpiContext::new(self.token_program.to_account_info(), account);
-        
+
         transfer_checked(ctx, amount, 6)
     }
 
@@ -4,7 +4,7 @@ use crate::{assert_non_zero, assert_not_expired, assert_not_locked};
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{burn, transfer, Burn, Mint, Token, TokenAccount, Transfer};
-use constant_product_curve::ConstantProduct;
+use fairswap_math::curve;
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -81,23 +81,19 @@ impl<'info> Withdraw<'info> {
         assert_not_expired!(expiration);
         assert_non_zero!([amount]);
 
-        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+        let (x, y) = curve::withdraw_amounts(
             self.vault_x.amount,
             self.vault_y.amount,
             self.mint_lp.supply,
             amount,
-            6,
         )
         .map_err(AmmError::from)?;
 
         // Check for slippage
-        require!(
-            min_x <= amounts.x && min_y <= amounts.y,
-            AmmError::SlippageExceeded
-        );
+        require!(min_x <= x && min_y <= y, AmmError::SlippageExceeded);
 
-        self.withdraw_tokens(true, amounts.x)?;
-        self.withdraw_tokens(false, amounts.y)?;
+        self.withdraw_tokens(true, x)?;
+        self.withdraw_tokens(false, y)?;
         self.burn_lp_tokens(amount)
     }
 
@@ -1,5 +1,5 @@
 use anchor_lang::error_code;
-use constant_product_curve::CurveError;
+use fairswap_math::MathError;
 
 #[error_code]
 pub enum AmmError {
@@ -43,16 +43,20 @@ pub enum AmmError {
     ZeroBalance,
 }
 
-impl From<CurveError> for AmmError {
-    fn from(error: CurveError) -> AmmError {
+impl From<MathError> for AmmError {
+    fn from(error: MathError) -> AmmError {
         match error {
-            CurveError::InvalidPrecision => AmmError::InvalidPrecision,
-            CurveError::Overflow => AmmError::Overflow,
-            CurveError::Underflow => AmmError::Underflow,
-            CurveError::InvalidFeeAmount => AmmError::InvalidFee,
-            CurveError::InsufficientBalance => AmmError::InsufficientBalance,
-            CurveError::ZeroBalance => AmmError::ZeroBalance,
-            CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded,
+            MathError::InvalidPrecision => AmmError::InvalidPrecision,
+            MathError::InvalidFee => AmmError::InvalidFee,
+            MathError::Overflow => AmmError::Overflow,
+            MathError::Underflow => AmmError::Underflow,
+            MathError::ZeroBalance => AmmError::ZeroBalance,
+            MathError::InsufficientBalance => AmmError::InsufficientBalance,
+            // simple_amm only trades on the constant product curve
+            MathError::InvalidAmp
+            | MathError::InvalidWeights
+            | MathError::NotConverged
+            | MathError::TradeTooLarge => AmmError::CurveError,
         }
     }
 }
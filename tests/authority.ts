@@ -0,0 +1,164 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Fairswap } from "../target/types/fairswap";
+
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js"
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token"
+import { randomBytes } from "crypto"
+import { expect } from "chai"
+import { confirmTx, confirmTxs, fairswapPdas, fetchEvents, newMintToAta } from "./utils";
+
+describe("fairswap authority management", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Fairswap as Program<Fairswap>;
+  const connection = anchor.getProvider().connection;
+  const [initializer, successor, outsider] = [new Keypair(), new Keypair(), new Keypair()];
+  const seed = new BN(randomBytes(8));
+
+  let mint_x: PublicKey;
+  let mint_y: PublicKey;
+  let pool: ReturnType<typeof fairswapPdas>;
+
+  const updateAccounts = (admin: PublicKey) => ({
+    admin,
+    mintX: mint_x,
+    mintY: mint_y,
+    config: pool.config,
+    pooldata: pool.pooldata,
+    systemProgram: SystemProgram.programId
+  });
+
+  const expectError = async (promise: Promise<string>, code: string) => {
+    try {
+      await promise;
+      expect.fail(`expected ${code}`);
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal(code);
+    }
+  };
+
+  it("Airdrop", async () => {
+    await Promise.all([initializer, successor, outsider].map((k) =>
+      connection.requestAirdrop(k.publicKey, 100 * anchor.web3.LAMPORTS_PER_SOL))).then(confirmTxs);
+  });
+
+  it("Initialize", async () => {
+    const [x, y] = await Promise.all([initializer, initializer].map((a) => newMintToAta(connection, a, 1e8)));
+    [mint_x, mint_y] = [x.mint, y.mint];
+    pool = fairswapPdas(program.programId, mint_x, mint_y, seed);
+
+    const tx = await program.methods.initialize(seed, 30, 0, new BN(1), initializer.publicKey, { lp: {} }, { constantProduct: {} }, new BN(0), 50, 50)
+      .accountsPartial({
+        admin: initializer.publicKey,
+        auth: pool.auth,
+        mintX: mint_x,
+        mintY: mint_y,
+        vaultX: pool.vaultX,
+        vaultY: pool.vaultY,
+        config: pool.config,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    const config = await program.account.config.fetch(pool.config);
+    expect(config.authority.equals(initializer.publicKey)).to.be.true;
+    expect(config.pendingAuthority).to.be.null;
+  });
+
+  it("Set fee", async () => {
+    const tx = await program.methods.setFee(50, 1000)
+      .accountsStrict(updateAccounts(initializer.publicKey))
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    const config = await program.account.config.fetch(pool.config);
+    expect(config.fee).to.equal(50);
+    expect(config.protocolFeeBps).to.equal(1000);
+
+    const [event] = await fetchEvents(program, tx);
+    expect(event.name).to.equal("feeUpdated");
+    expect(event.data.oldFee).to.equal(30);
+    expect(event.data.newFee).to.equal(50);
+    expect(event.data.newProtocolFeeBps).to.equal(1000);
+  });
+
+  it("Fail to set fee without the authority", async () => {
+    await expectError(program.methods.setFee(0, 0)
+      .accountsStrict(updateAccounts(outsider.publicKey))
+      .signers([outsider]).rpc(), "Unauthorized");
+  });
+
+  it("Fail to set fee above 100%", async () => {
+    await expectError(program.methods.setFee(10000, 0)
+      .accountsStrict(updateAccounts(initializer.publicKey))
+      .signers([initializer]).rpc(), "InvalidFeeSet");
+  });
+
+  it("Propose authority", async () => {
+    const tx = await program.methods.proposeAuthority(successor.publicKey)
+      .accountsStrict(updateAccounts(initializer.publicKey))
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    const config = await program.account.config.fetch(pool.config);
+    expect(config.authority.equals(initializer.publicKey)).to.be.true;
+    expect(config.pendingAuthority.equals(successor.publicKey)).to.be.true;
+
+    const [event] = await fetchEvents(program, tx);
+    expect(event.name).to.equal("authorityProposed");
+    expect(event.data.pendingAuthority.equals(successor.publicKey)).to.be.true;
+  });
+
+  it("Fail to accept authority as someone else", async () => {
+    await expectError(program.methods.acceptAuthority()
+      .accountsStrict(updateAccounts(outsider.publicKey))
+      .signers([outsider]).rpc(), "Unauthorized");
+  });
+
+  it("Accept authority", async () => {
+    const tx = await program.methods.acceptAuthority()
+      .accountsStrict(updateAccounts(successor.publicKey))
+      .signers([successor]).rpc();
+    await confirmTx(tx);
+
+    const config = await program.account.config.fetch(pool.config);
+    expect(config.authority.equals(successor.publicKey)).to.be.true;
+    expect(config.pendingAuthority).to.be.null;
+
+    const [event] = await fetchEvents(program, tx);
+    expect(event.name).to.equal("authorityAccepted");
+    expect(event.data.oldAuthority.equals(initializer.publicKey)).to.be.true;
+    expect(event.data.newAuthority.equals(successor.publicKey)).to.be.true;
+  });
+
+  it("Fail to lock with the previous authority", async () => {
+    await expectError(program.methods.lock()
+      .accountsStrict(updateAccounts(initializer.publicKey))
+      .signers([initializer]).rpc(), "Unauthorized");
+  });
+
+  it("Renounce authority", async () => {
+    const tx = await program.methods.renounceAuthority()
+      .accountsStrict(updateAccounts(successor.publicKey))
+      .signers([successor]).rpc();
+    await confirmTx(tx);
+
+    const config = await program.account.config.fetch(pool.config);
+    expect(config.authority).to.be.null;
+    expect(config.protocolFeeBps).to.equal(0);
+
+    const [event] = await fetchEvents(program, tx);
+    expect(event.name).to.equal("authorityRenounced");
+    expect(event.data.authority.equals(successor.publicKey)).to.be.true;
+  });
+
+  it("Fail to set fee once renounced", async () => {
+    await expectError(program.methods.setFee(30, 0)
+      .accountsStrict(updateAccounts(successor.publicKey))
+      .signers([successor]).rpc(), "NoAuthoritySet");
+  });
+});
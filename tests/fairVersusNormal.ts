@@ -8,7 +8,7 @@ import { TOKEN_PROGRAM_ID as tokenProgram, getAssociatedTokenAddress, TOKEN_PROG
 import { randomBytes } from "crypto"
 import { assert, expect } from "chai"
 import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
-import { buildTxConfirmOrLog, confirmTx, confirmTxs, createAndFundATA, logBalances, newMintToAta } from "./utils";
+import { buildTxConfirmOrLog, confirmTx, confirmTxs, createAndFundATA, logBalances, newMintToAta, deadlineIn } from "./utils";
 
 // herşeyi initle ve mintle
 // 2 borsayı da initle
@@ -24,14 +24,16 @@ describe("fairswap compared to regular amm", () => {
 
     // Random seed
     const seed = new BN(randomBytes(8));
-    const auth_fairswap = PublicKey.findProgramAddressSync([Buffer.from("auth")], program_fairswap.programId)[0];
     const auth_normal = PublicKey.findProgramAddressSync([Buffer.from("auth")], program_normal.programId)[0];
 
     let mint_x: PublicKey;
     let mint_y: PublicKey;
     let mint_lp_fairswap: PublicKey;
+    let locked_lp_fairswap: PublicKey;
     let mint_lp_normal: PublicKey;
     let config_fairswap: PublicKey;
+    let auth_fairswap: PublicKey;
+    let pooldata_fairswap: PublicKey;
     let config_normal: PublicKey;
     let initializer_x_ata: PublicKey;
     let initializer_y_ata: PublicKey;
@@ -43,7 +45,6 @@ describe("fairswap compared to regular amm", () => {
     let user2_y_ata: PublicKey;
     let vault_x_ata_fairswap: PublicKey;
     let vault_y_ata_fairswap: PublicKey;
-    let vault_lp_ata_fairswap: PublicKey;
     let vault_x_ata_normal: PublicKey;
     let vault_y_ata_normal: PublicKey;
     let vault_lp_ata_normal: PublicKey;
@@ -71,13 +72,15 @@ describe("fairswap compared to regular amm", () => {
 
         // create config and lp mint for fairswap
         config_fairswap = PublicKey.findProgramAddressSync([Buffer.from("config"), mint_x.toBuffer(), mint_y.toBuffer(), seed.toBuffer().reverse()], program_fairswap.programId)[0];
+        auth_fairswap = PublicKey.findProgramAddressSync([Buffer.from("auth"), config_fairswap.toBuffer()], program_fairswap.programId)[0];
+        pooldata_fairswap = PublicKey.findProgramAddressSync([Buffer.from("pooldata"), config_fairswap.toBuffer()], program_fairswap.programId)[0];
         mint_lp_fairswap = PublicKey.findProgramAddressSync([Buffer.from("mint_lp"), config_fairswap.toBuffer()], program_fairswap.programId)[0];
+        locked_lp_fairswap = PublicKey.findProgramAddressSync([Buffer.from("locked_lp"), config_fairswap.toBuffer()], program_fairswap.programId)[0];
         initializer_lp_ata_fairswap = await getAssociatedTokenAddress(mint_lp_fairswap, initializer.publicKey, false, tokenProgram);
 
-        // Create take ATAs
-        vault_x_ata_fairswap = await getAssociatedTokenAddress(mint_x, auth_fairswap, true, tokenProgram);
-        vault_y_ata_fairswap = await getAssociatedTokenAddress(mint_y, auth_fairswap, true, tokenProgram);
-        vault_lp_ata_fairswap = await getAssociatedTokenAddress(mint_lp_fairswap, auth_fairswap, true, tokenProgram);
+        // Fairswap vaults are PDAs owned by the per-pool auth
+        vault_x_ata_fairswap = PublicKey.findProgramAddressSync([Buffer.from("vault"), config_fairswap.toBuffer(), mint_x.toBuffer()], program_fairswap.programId)[0];
+        vault_y_ata_fairswap = PublicKey.findProgramAddressSync([Buffer.from("vault"), config_fairswap.toBuffer(), mint_y.toBuffer()], program_fairswap.programId)[0];
 
         // create config and lp mint for normal amm
         config_normal = PublicKey.findProgramAddressSync([Buffer.from("config"), seed.toBuffer().reverse()], program_normal.programId)[0];
@@ -109,7 +112,15 @@ describe("fairswap compared to regular amm", () => {
 
         const tx = await program_fairswap.methods.initialize(
                 seed,
-                0
+                0,
+                0,
+                new BN(1),
+                initializer.publicKey,
+                { lp: {} },
+                { constantProduct: {} },
+                new BN(0),
+                50,
+                50
             )
             .accountsPartial({
                 admin: initializer.publicKey,
@@ -119,8 +130,8 @@ describe("fairswap compared to regular amm", () => {
                 vaultX: vault_x_ata_fairswap,
                 vaultY: vault_y_ata_fairswap,
                 config: config_fairswap,
-                tokenProgram: TOKEN_PROGRAM_ID,
-                associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+                tokenProgramX: TOKEN_PROGRAM_ID,
+                tokenProgramY: TOKEN_PROGRAM_ID,
                 systemProgram: SystemProgram.programId
             })
             .signers([
@@ -159,10 +170,12 @@ describe("fairswap compared to regular amm", () => {
     const amountDepositLP = 2e6;
 
     it("Deposit to Both AMMs", async () => {
+        // fairswap locks MINIMUM_LIQUIDITY out of the first deposit's LP
         const tx = await program_fairswap.methods.deposit(
-                new BN(amountDepositLP),
+                new BN(amountDepositLP - 1000),
                 new BN(amountDepositX),
-                new BN(amountDepositY)
+                new BN(amountDepositY),
+                deadlineIn(600)
             )
             .accountsStrict({
                 user: initializer.publicKey,
@@ -172,11 +185,15 @@ describe("fairswap compared to regular amm", () => {
                 userAtaX: initializer_x_ata,
                 userAtaY: initializer_y_ata,
                 userAtaLp: initializer_lp_ata_fairswap,
+                lockedLp: locked_lp_fairswap,
                 vaultX: vault_x_ata_fairswap,
                 vaultY: vault_y_ata_fairswap,
                 mintLp: mint_lp_fairswap,
                 config: config_fairswap,
-                tokenProgram: TOKEN_PROGRAM_ID,
+                pooldata: pooldata_fairswap,
+                tokenProgramX: TOKEN_PROGRAM_ID,
+                tokenProgramY: TOKEN_PROGRAM_ID,
+                tokenProgramLp: TOKEN_PROGRAM_ID,
                 associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
                 systemProgram: SystemProgram.programId
             })
@@ -227,7 +244,8 @@ describe("fairswap compared to regular amm", () => {
         const tx = await program_fairswap.methods.swap(
             mint_x,
             new BN(amountSwapX),
-            new BN(amountSwapYMin)
+            new BN(amountSwapYMin),
+            deadlineIn(600)
         )
             .accountsPartial({
                 auth: auth_fairswap,
@@ -239,7 +257,8 @@ describe("fairswap compared to regular amm", () => {
                 vaultX: vault_x_ata_fairswap,
                 vaultY: vault_y_ata_fairswap,
                 config: config_fairswap,
-                tokenProgram: TOKEN_PROGRAM_ID,
+                tokenProgramX: TOKEN_PROGRAM_ID,
+                tokenProgramY: TOKEN_PROGRAM_ID,
                 associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
                 systemProgram: SystemProgram.programId
             })
@@ -370,16 +389,17 @@ describe("fairswap compared to regular amm", () => {
             vaultX: vault_x_ata_fairswap,
             vaultY: vault_y_ata_fairswap,
             config: config_fairswap,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId
         };
 
-        const ix1 = await program_fairswap.methods.swap(mint_x, new BN(15000), new BN(1000))
+        const ix1 = await program_fairswap.methods.swap(mint_x, new BN(15000), new BN(1000), deadlineIn(600))
             .accountsPartial(fairswapSwapAccounts).instruction();
-        const ix2 = await program_fairswap.methods.swap(mint_x, new BN(15000), new BN(1000))
+        const ix2 = await program_fairswap.methods.swap(mint_x, new BN(15000), new BN(1000), deadlineIn(600))
             .accountsPartial(fairswapSwapAccounts).instruction();
-        const ix3 = await program_fairswap.methods.swap(mint_y, new BN(30000), new BN(1000))
+        const ix3 = await program_fairswap.methods.swap(mint_y, new BN(30000), new BN(1000), deadlineIn(600))
             .accountsPartial(fairswapSwapAccounts).instruction();
 
         const fairswapTx = new anchor.web3.Transaction().add(ix1, ix2, ix3);
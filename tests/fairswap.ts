@@ -7,7 +7,7 @@ import { TOKEN_PROGRAM_ID as tokenProgram, getAssociatedTokenAddress, TOKEN_PROG
 import { randomBytes } from "crypto"
 import { assert, expect } from "chai"
 import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
-import { confirmTx, confirmTxs, logBalances, newMintToAta } from "./utils";
+import { confirmTx, confirmTxs, fetchEvents, logBalances, newMintToAta, deadlineIn } from "./utils";
 
 
 describe("fairswap general amm functions", () => {
@@ -19,12 +19,14 @@ describe("fairswap general amm functions", () => {
 
   // Random seed
   const seed = new BN(randomBytes(8));
-  const auth = PublicKey.findProgramAddressSync([Buffer.from("auth")], program.programId)[0];
 
   let mint_x: PublicKey;
   let mint_y: PublicKey;
   let mint_lp: PublicKey;
+  let locked_lp: PublicKey;
   let config: PublicKey;
+  let auth: PublicKey;
+  let pooldata: PublicKey;
   let initializer_x_ata: PublicKey;
   let initializer_y_ata: PublicKey;
   let initializer_lp_ata: PublicKey;
@@ -34,7 +36,6 @@ describe("fairswap general amm functions", () => {
   let user2_y_ata: PublicKey;
   let vault_x_ata: PublicKey;
   let vault_y_ata: PublicKey;
-  let vault_lp_ata: PublicKey;
 
   // ATAs
   it("Airdrop", async () => {
@@ -58,13 +59,15 @@ describe("fairswap general amm functions", () => {
     // user2_y_ata = await createAndFundATA(anchor.getProvider().connection, initializer, mint_y, user2.publicKey, 0);
 
     config = PublicKey.findProgramAddressSync([Buffer.from("config"), mint_x.toBuffer(), mint_y.toBuffer(), seed.toBuffer().reverse()], program.programId)[0];
+    auth = PublicKey.findProgramAddressSync([Buffer.from("auth"), config.toBuffer()], program.programId)[0];
+    pooldata = PublicKey.findProgramAddressSync([Buffer.from("pooldata"), config.toBuffer()], program.programId)[0];
     mint_lp = PublicKey.findProgramAddressSync([Buffer.from("mint_lp"), config.toBuffer()], program.programId)[0];
+    locked_lp = PublicKey.findProgramAddressSync([Buffer.from("locked_lp"), config.toBuffer()], program.programId)[0];
     initializer_lp_ata = await getAssociatedTokenAddress(mint_lp, initializer.publicKey, false, tokenProgram);
 
-    // Create take ATAs
-    vault_x_ata = await getAssociatedTokenAddress(mint_x, auth, true, tokenProgram);
-    vault_y_ata = await getAssociatedTokenAddress(mint_y, auth, true, tokenProgram);
-    vault_lp_ata = await getAssociatedTokenAddress(mint_lp, auth, true, tokenProgram);
+    // Pool vaults are PDAs owned by the per-pool auth
+    vault_x_ata = PublicKey.findProgramAddressSync([Buffer.from("vault"), config.toBuffer(), mint_x.toBuffer()], program.programId)[0];
+    vault_y_ata = PublicKey.findProgramAddressSync([Buffer.from("vault"), config.toBuffer(), mint_y.toBuffer()], program.programId)[0];
   })
 
   it("Initialize", async () => {
@@ -74,10 +77,17 @@ describe("fairswap general amm functions", () => {
     console.log(`Config is ${config.toBase58()}`);
     console.log(`Vault X is ${vault_x_ata.toBase58()}`);
     console.log(`Vault Y is ${vault_y_ata.toBase58()}`);
-    console.log(`Vault LP is ${vault_lp_ata.toBase58()}`);
     const tx = await program.methods.initialize(
       seed,
-      0
+      0,
+      0,
+      new BN(1),
+      initializer.publicKey,
+      { lp: {} },
+      { constantProduct: {} },
+      new BN(0),
+      50,
+      50
     )
       // .accounts({
       //   admin: initializer.publicKey,
@@ -100,8 +110,8 @@ describe("fairswap general amm functions", () => {
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
         config,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId
       })
       .signers([
@@ -127,6 +137,10 @@ describe("fairswap general amm functions", () => {
       ]).rpc();
     await confirmTx(tx);
     console.log("Your transaction signature", tx);
+
+    const [event] = await fetchEvents(program, tx);
+    expect(event.name).to.equal("poolLocked");
+    expect(event.data.config.equals(config)).to.be.true;
   });
 
   it("Fail to deposit during lock", async () => {
@@ -134,7 +148,8 @@ describe("fairswap general amm functions", () => {
       const tx = await program.methods.deposit(
         new BN(2e5),
         new BN(2e5),
-        new BN(3e5)
+        new BN(3e5),
+        deadlineIn(600)
       )
         .accountsStrict({
           user: initializer.publicKey,
@@ -144,11 +159,15 @@ describe("fairswap general amm functions", () => {
           userAtaX: initializer_x_ata,
           userAtaY: initializer_y_ata,
           userAtaLp: initializer_lp_ata,
+          lockedLp: locked_lp,
           vaultX: vault_x_ata,
           vaultY: vault_y_ata,
           mintLp: mint_lp,
           config,
-          tokenProgram: TOKEN_PROGRAM_ID,
+          pooldata,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          tokenProgramLp: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId
         })
@@ -236,11 +255,96 @@ describe("fairswap general amm functions", () => {
     }
   });
 
+  it("Set window slots", async () => {
+    const tx = await program.methods.setWindowSlots(new BN(2))
+      .accountsPartial({
+        admin: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        config,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+    await confirmTx(tx);
+    console.log("Your transaction signature", tx);
+
+    const pooldataAccount = await program.account.poolData.fetch(pooldata);
+    expect(pooldataAccount.windowSlots.toNumber()).to.equal(2);
+  });
+
+  it("Fail to set window slots", async () => {
+    try {
+      const tx = await program.methods.setWindowSlots(new BN(10))
+        .accountsPartial({
+          admin: user1.publicKey,
+          mintX: mint_x,
+          mintY: mint_y,
+          config,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([
+          user1
+        ]).rpc();
+      console.log("Your transaction signature", tx);
+      assert.fail("Transaction should have failed but succeeded");
+    } catch (e) {
+      let err = e as anchor.AnchorError;
+      if (err.error.errorCode.code !== "Unauthorized") {
+        throw (e)
+      }
+    }
+  });
+
+  it("Fail to mint more LP than sqrt(x * y) on the first deposit", async () => {
+    try {
+      // sqrt(2e5 * 3e5) is ~244948, less 1000 locked
+      const tx = await program.methods.deposit(
+        new BN(1e6),
+        new BN(2e5),
+        new BN(3e5),
+        deadlineIn(600)
+      )
+        .accountsStrict({
+          user: initializer.publicKey,
+          auth,
+          mintX: mint_x,
+          mintY: mint_y,
+          userAtaX: initializer_x_ata,
+          userAtaY: initializer_y_ata,
+          userAtaLp: initializer_lp_ata,
+          lockedLp: locked_lp,
+          vaultX: vault_x_ata,
+          vaultY: vault_y_ata,
+          mintLp: mint_lp,
+          config,
+          pooldata,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          tokenProgramLp: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([
+          initializer
+        ]).rpc();
+      console.log("Your transaction signature", tx);
+      assert.fail("Transaction should have failed but succeeded");
+    } catch (e) {
+      let err = e as anchor.AnchorError;
+      if (err.error.errorCode.code !== "SlippageExceeded") {
+        throw (e)
+      }
+    }
+  });
+
   it("Deposit", async () => {
     const tx = await program.methods.deposit(
       new BN(2e5),
       new BN(2e5),
-      new BN(3e5)
+      new BN(3e5),
+      deadlineIn(600)
     )
       .accountsStrict({
         user: initializer.publicKey,
@@ -250,11 +354,15 @@ describe("fairswap general amm functions", () => {
         userAtaX: initializer_x_ata,
         userAtaY: initializer_y_ata,
         userAtaLp: initializer_lp_ata,
+        lockedLp: locked_lp,
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
         mintLp: mint_lp,
         config,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        pooldata,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
+        tokenProgramLp: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
       })
@@ -264,13 +372,85 @@ describe("fairswap general amm functions", () => {
     await confirmTx(tx);
     console.log("Your deposit transaction signature", tx);
     await logBalances(initializer.publicKey, "deposit", mint_x, mint_y);
+
+    const connection = anchor.getProvider().connection;
+    const [lp, locked] = await Promise.all([initializer_lp_ata, locked_lp].map((a) => connection.getTokenAccountBalance(a)));
+    expect(lp.value.amount).to.equal("243948");
+    expect(locked.value.amount).to.equal("1000");
+
+    // the deposit opens the first fairness window at its own price
+    const data = await program.account.poolData.fetch(pooldata);
+    expect(data.buyingXHigh.toString()).to.equal("1500000");
+    expect(data.buyingYHigh.toString()).to.equal("666666");
+
+    const [event] = await fetchEvents(program, tx);
+    expect(event.name).to.equal("liquidityAdded");
+    expect(event.data.lpAmount.toNumber()).to.equal(243948);
+    expect(event.data.lpSupply.toNumber()).to.equal(244948);
+  });
+
+  it("Quote X for Y", async () => {
+    const quote = await program.methods.quote(
+      mint_x,
+      new BN(5000),
+      new BN(1)
+    )
+      .accountsPartial({
+        mintX: mint_x,
+        mintY: mint_y,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        config,
+        pooldata,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID
+      })
+      .view();
+    console.log(`Quote: raw out ${quote.amountOutRaw.toString()}, fair out ${quote.amountOut.toString()}, ratio ${quote.ratio.toString()}`);
+    expect(quote.amountIn.toNumber()).to.equal(5000);
+    expect(quote.amountOut.lte(quote.amountOutRaw)).to.be.true;
+    expect(quote.windowSlots.toNumber()).to.equal(2);
+  });
+
+  it("Fail to swap after the deadline", async () => {
+    const currentSlot = await anchor.getProvider().connection.getSlot();
+    for (const deadline of [deadlineIn(-60), { slot: [new BN(currentSlot - 1)] }]) {
+      try {
+        await program.methods.swap(mint_x, new BN(5000), new BN(1), deadline)
+          .accountsPartial({
+            auth,
+            user: initializer.publicKey,
+            mintX: mint_x,
+            mintY: mint_y,
+            userAtaX: initializer_x_ata,
+            userAtaY: initializer_y_ata,
+            vaultX: vault_x_ata,
+            vaultY: vault_y_ata,
+            config,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+            systemProgram: SystemProgram.programId
+          })
+          .signers([
+            initializer
+          ]).rpc();
+        assert.fail("Transaction should have failed but succeeded");
+      } catch (e) {
+        let err = e as anchor.AnchorError;
+        if (err.error.errorCode.code !== "Expired") {
+          throw (e)
+        }
+      }
+    }
   });
 
   it("Swap X for Y", async () => {
     const tx = await program.methods.swap(
       mint_x,
       new BN(5000),
-      new BN(6000)
+      new BN(6000),
+      deadlineIn(600)
     )
       .accountsPartial({
         auth,
@@ -282,7 +462,8 @@ describe("fairswap general amm functions", () => {
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
         config,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
       })
@@ -294,13 +475,21 @@ describe("fairswap general amm functions", () => {
     const currentSlot = await anchor.getProvider().connection.getSlot();
     console.log(`Current slot is ${currentSlot}`);
     await logBalances(initializer.publicKey, "swap X for Y", mint_x, mint_y);
+
+    const [event] = await fetchEvents(program, tx);
+    expect(event.name).to.equal("swapped");
+    expect(event.data.mintIn.equals(mint_x)).to.be.true;
+    expect(event.data.amountIn.toNumber()).to.equal(5000);
+    expect(event.data.amountOut.lte(event.data.amountOutRaw)).to.be.true;
+    expect(event.data.buyingYHigh.toString()).to.equal(event.data.ratio.toString());
   });
 
   it("Swap Y for X", async () => {
     const tx = await program.methods.swap(
       mint_y,
       new BN(7330),
-      new BN(4500)
+      new BN(4500),
+      deadlineIn(600)
     )
       .accountsPartial({
         auth,
@@ -312,7 +501,8 @@ describe("fairswap general amm functions", () => {
         vaultX: vault_x_ata,
         vaultY: vault_y_ata,
         config,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
       })
@@ -326,6 +516,39 @@ describe("fairswap general amm functions", () => {
     await logBalances(initializer.publicKey, "swap Y for X", mint_x, mint_y);
   });
 
+  it("Swap X for exactly 1000 Y", async () => {
+    const before = await logBalances(initializer.publicKey, "before exact out swap", mint_x, mint_y);
+    const tx = await program.methods.swapExactOut(
+      mint_x,
+      new BN(1000),
+      new BN(5000),
+      deadlineIn(600)
+    )
+      .accountsPartial({
+        auth,
+        user: initializer.publicKey,
+        mintX: mint_x,
+        mintY: mint_y,
+        userAtaX: initializer_x_ata,
+        userAtaY: initializer_y_ata,
+        vaultX: vault_x_ata,
+        vaultY: vault_y_ata,
+        config,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        initializer
+      ]).rpc();
+    await confirmTx(tx);
+    console.log("Your transaction signature", tx);
+    const after = await logBalances(initializer.publicKey, "after exact out swap", mint_x, mint_y);
+    expect(after.balanceY.sub(before.balanceY).toNumber()).to.equal(1000);
+    expect(before.balanceX.sub(after.balanceX).toNumber()).to.be.at.most(5000);
+  });
+
   it("Sandwich Self", async () => {
     const swapAccounts = {
       auth,
@@ -337,7 +560,8 @@ describe("fairswap general amm functions", () => {
       vaultX: vault_x_ata,
       vaultY: vault_y_ata,
       config,
-      tokenProgram: TOKEN_PROGRAM_ID,
+      tokenProgramX: TOKEN_PROGRAM_ID,
+      tokenProgramY: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
       systemProgram: SystemProgram.programId
     }
@@ -345,19 +569,22 @@ describe("fairswap general amm functions", () => {
     const ix1 = await program.methods.swap(
       mint_y,
       new BN(15000),
-      new BN(800)
+      new BN(800),
+      deadlineIn(600)
     ).accountsPartial(swapAccounts).instruction();
 
     const ix2 = await program.methods.swap(
       mint_y,
       new BN(15000),
-      new BN(800)
+      new BN(800),
+      deadlineIn(600)
     ).accountsPartial(swapAccounts).instruction();
 
     const ix3 = await program.methods.swap(
       mint_x,
       new BN(18191),
-      new BN(800)
+      new BN(800),
+      deadlineIn(600)
     ).accountsPartial(swapAccounts).instruction();
     const tx = new anchor.web3.Transaction().add(ix1, ix2, ix3);
     const txSignature = await anchor.web3.sendAndConfirmTransaction(
@@ -388,6 +615,11 @@ describe("fairswap general amm functions", () => {
     const currentSlot = await anchor.getProvider().connection.getSlot();
     console.log(`Current slot is ${currentSlot}`);
     await logBalances(initializer.publicKey, "sandwich self", mint_x, mint_y);
+
+    // the back-run is clamped to the window's high-water mark, the difference is tracked as surplus
+    const pooldataAccount = await program.account.poolData.fetch(pooldata);
+    console.log(`Surplus X: ${pooldataAccount.surplusX.toString()}, surplus Y: ${pooldataAccount.surplusY.toString()}`);
+    expect(pooldataAccount.surplusY.gtn(0)).to.be.true;
   });
 
   xit("real mev", async () => {
@@ -406,7 +638,8 @@ describe("fairswap general amm functions", () => {
       vaultX: vault_x_ata,
       vaultY: vault_y_ata,
       config,
-      tokenProgram: TOKEN_PROGRAM_ID,
+      tokenProgramX: TOKEN_PROGRAM_ID,
+      tokenProgramY: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
       systemProgram: SystemProgram.programId
     }
@@ -414,7 +647,8 @@ describe("fairswap general amm functions", () => {
     const tx_user1_1 = await program.methods.swap(
       mint_y,
       new BN(1500),
-      new BN(80)
+      new BN(80),
+      deadlineIn(600)
     ).accountsPartial({
       ...swapAccounts,
       userAtaX: user1_x_ata,
@@ -424,7 +658,8 @@ describe("fairswap general amm functions", () => {
     const tx_user2_1 = await program.methods.swap(
       mint_y,
       new BN(1500),
-      new BN(80)
+      new BN(80),
+      deadlineIn(600)
     ).accountsPartial({
       ...swapAccounts,
       userAtaX: user2_x_ata,
@@ -434,7 +669,8 @@ describe("fairswap general amm functions", () => {
     const tx_user1_2 = await program.methods.swap(
       mint_x,
       new BN(900),
-      new BN(80)
+      new BN(80),
+      deadlineIn(600)
     ).accountsPartial({
       ...swapAccounts,
       userAtaX: user1_x_ata,
@@ -464,7 +700,8 @@ describe("fairswap general amm functions", () => {
     const tx = await program.methods.withdraw(
       new BN(2e5),
       new BN(2e5 * 0.49),
-      new BN(3e5 * 0.49)
+      new BN(3e5 * 0.49),
+      deadlineIn(600)
     )
       .accountsStrict({
         user: initializer.publicKey,
@@ -478,7 +715,10 @@ describe("fairswap general amm functions", () => {
         vaultY: vault_y_ata,
         mintLp: mint_lp,
         config,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        pooldata,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
+        tokenProgramLp: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId
       })
@@ -488,6 +728,12 @@ describe("fairswap general amm functions", () => {
     await confirmTx(tx);
     console.log("Your withdraw transaction signature", tx);
     await logBalances(initializer.publicKey, "withdraw", mint_x, mint_y);
+
+    // every swap, deposit and withdraw since the first deposit fed the price accumulators
+    const pooldataAccount = await program.account.poolData.fetch(pooldata);
+    console.log(`Price X cumulative: ${pooldataAccount.priceXCumulative.toString()}, price Y cumulative: ${pooldataAccount.priceYCumulative.toString()}`);
+    expect(pooldataAccount.oracleTimestamp.gtn(0)).to.be.true;
+    expect(pooldataAccount.priceXCumulative.gtn(0)).to.be.true;
   });
 });
 
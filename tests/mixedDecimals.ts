@@ -0,0 +1,130 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Fairswap } from "../target/types/fairswap";
+
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js"
+import { TOKEN_PROGRAM_ID, getAssociatedTokenAddress, getMint } from "@solana/spl-token"
+import { randomBytes } from "crypto"
+import { expect } from "chai"
+import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
+import { confirmTx, confirmTxs, fairswapPdas, logBalances, newMintToAta, deadlineIn } from "./utils";
+
+// [decimals of X, decimals of Y]
+const pairs: [number, number][] = [[9, 6], [6, 8]];
+
+pairs.forEach(([decimalsX, decimalsY]) => {
+  describe(`fairswap with ${decimalsX}/${decimalsY} decimal mints`, () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+
+    const program = anchor.workspace.Fairswap as Program<Fairswap>;
+    const initializer = new Keypair();
+    const seed = new BN(randomBytes(8));
+
+    const unitX = new BN(10).pow(new BN(decimalsX));
+    const unitY = new BN(10).pow(new BN(decimalsY));
+
+    let mint_x: PublicKey;
+    let mint_y: PublicKey;
+    let initializer_x_ata: PublicKey;
+    let initializer_y_ata: PublicKey;
+    let initializer_lp_ata: PublicKey;
+    let pool: ReturnType<typeof fairswapPdas>;
+
+    const poolAccounts = () => ({
+      user: initializer.publicKey,
+      auth: pool.auth,
+      mintX: mint_x,
+      mintY: mint_y,
+      userAtaX: initializer_x_ata,
+      userAtaY: initializer_y_ata,
+      userAtaLp: initializer_lp_ata,
+      lockedLp: pool.lockedLp,
+      vaultX: pool.vaultX,
+      vaultY: pool.vaultY,
+      mintLp: pool.mintLp,
+      config: pool.config,
+      pooldata: pool.pooldata,
+      tokenProgramX: TOKEN_PROGRAM_ID,
+      tokenProgramY: TOKEN_PROGRAM_ID,
+      tokenProgramLp: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+      systemProgram: SystemProgram.programId
+    });
+
+    it("Airdrop", async () => {
+      await anchor.getProvider().connection.requestAirdrop(initializer.publicKey, 100 * anchor.web3.LAMPORTS_PER_SOL).then(confirmTx);
+    });
+
+    it("Create mints and initialize", async () => {
+      const connection = anchor.getProvider().connection;
+      const x = await newMintToAta(connection, initializer, BigInt(unitX.muln(1000).toString()), decimalsX);
+      const y = await newMintToAta(connection, initializer, BigInt(unitY.muln(1000).toString()), decimalsY);
+      [mint_x, initializer_x_ata] = [x.mint, x.ata];
+      [mint_y, initializer_y_ata] = [y.mint, y.ata];
+      pool = fairswapPdas(program.programId, mint_x, mint_y, seed);
+      initializer_lp_ata = await getAssociatedTokenAddress(pool.mintLp, initializer.publicKey);
+
+      const tx = await program.methods.initialize(seed, 30, 0, new BN(1), initializer.publicKey, { lp: {} }, { constantProduct: {} }, new BN(0), 50, 50)
+        .accountsPartial({
+          admin: initializer.publicKey,
+          auth: pool.auth,
+          mintX: mint_x,
+          mintY: mint_y,
+          vaultX: pool.vaultX,
+          vaultY: pool.vaultY,
+          config: pool.config,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([initializer]).rpc();
+      await confirmTx(tx);
+
+      const pooldata = await program.account.poolData.fetch(pool.pooldata);
+      expect(pooldata.precision).to.equal(6 + Math.abs(decimalsX - decimalsY));
+    });
+
+    it("Deposit 100 X and 200 Y", async () => {
+      const tx = await program.methods.deposit(new BN(10).pow(new BN(Math.floor((decimalsX + decimalsY) / 2))).muln(100), unitX.muln(100), unitY.muln(200), deadlineIn(600))
+        .accountsStrict(poolAccounts())
+        .signers([initializer]).rpc();
+      await confirmTx(tx);
+
+      const mintLp = await getMint(anchor.getProvider().connection, pool.mintLp);
+      expect(mintLp.decimals).to.equal(Math.floor((decimalsX + decimalsY) / 2));
+      await logBalances(initializer.publicKey, "deposit", mint_x, mint_y);
+    });
+
+    it("Swap 1 X for Y", async () => {
+      const before = await logBalances(initializer.publicKey, "before swap X for Y", mint_x, mint_y);
+      const tx = await program.methods.swap(mint_x, unitX, unitY.muln(19).divn(10), deadlineIn(600))
+        .accountsPartial(poolAccounts())
+        .signers([initializer]).rpc();
+      await confirmTx(tx);
+      const after = await logBalances(initializer.publicKey, "after swap X for Y", mint_x, mint_y);
+      expect(before.balanceX.sub(after.balanceX).eq(unitX)).to.be.true;
+      // 1 X is worth ~2 Y, less the fee and price impact
+      expect(after.balanceY.sub(before.balanceY).gte(unitY.muln(19).divn(10))).to.be.true;
+    });
+
+    it("Swap 2 Y for X", async () => {
+      const before = await logBalances(initializer.publicKey, "before swap Y for X", mint_x, mint_y);
+      const tx = await program.methods.swap(mint_y, unitY.muln(2), unitX.muln(9).divn(10), deadlineIn(600))
+        .accountsPartial(poolAccounts())
+        .signers([initializer]).rpc();
+      await confirmTx(tx);
+      const after = await logBalances(initializer.publicKey, "after swap Y for X", mint_x, mint_y);
+      expect(before.balanceY.sub(after.balanceY).eq(unitY.muln(2))).to.be.true;
+      expect(after.balanceX.sub(before.balanceX).gte(unitX.muln(9).divn(10))).to.be.true;
+    });
+
+    it("Withdraw half", async () => {
+      const lpBalance = new BN((await anchor.getProvider().connection.getTokenAccountBalance(initializer_lp_ata)).value.amount);
+      const tx = await program.methods.withdraw(lpBalance.divn(2), unitX.muln(49), unitY.muln(98), deadlineIn(600))
+        .accountsStrict(poolAccounts())
+        .signers([initializer]).rpc();
+      await confirmTx(tx);
+      await logBalances(initializer.publicKey, "withdraw", mint_x, mint_y);
+    });
+  });
+});
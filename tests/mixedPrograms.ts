@@ -0,0 +1,119 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Fairswap } from "../target/types/fairswap";
+
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js"
+import { TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo, getAssociatedTokenAddressSync, getAccount } from "@solana/spl-token"
+import { randomBytes } from "crypto"
+import { expect } from "chai"
+import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
+import { confirmTx, fairswapPdas, deadlineIn } from "./utils";
+
+// legacy SPL X against Token-2022 Y, LP minted under the legacy program
+describe("fairswap with a legacy and a Token-2022 mint", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Fairswap as Program<Fairswap>;
+  const connection = anchor.getProvider().connection;
+  const initializer = new Keypair();
+  const seed = new BN(randomBytes(8));
+
+  let mint_x: PublicKey;
+  let mint_y: PublicKey;
+  let initializer_x_ata: PublicKey;
+  let initializer_y_ata: PublicKey;
+  let initializer_lp_ata: PublicKey;
+  let pool: ReturnType<typeof fairswapPdas>;
+
+  const balance = async (account: PublicKey, tokenProgram: PublicKey) =>
+    new BN((await getAccount(connection, account, "confirmed", tokenProgram)).amount.toString());
+
+  const poolAccounts = () => ({
+    user: initializer.publicKey,
+    auth: pool.auth,
+    mintX: mint_x,
+    mintY: mint_y,
+    userAtaX: initializer_x_ata,
+    userAtaY: initializer_y_ata,
+    userAtaLp: initializer_lp_ata,
+    lockedLp: pool.lockedLp,
+    vaultX: pool.vaultX,
+    vaultY: pool.vaultY,
+    mintLp: pool.mintLp,
+    config: pool.config,
+    pooldata: pool.pooldata,
+    tokenProgramX: TOKEN_PROGRAM_ID,
+    tokenProgramY: TOKEN_2022_PROGRAM_ID,
+    tokenProgramLp: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Airdrop", async () => {
+    await connection.requestAirdrop(initializer.publicKey, 100 * anchor.web3.LAMPORTS_PER_SOL).then(confirmTx);
+  });
+
+  it("Create mints and initialize", async () => {
+    mint_x = await createMint(connection, initializer, initializer.publicKey, null, 6, undefined, { commitment: "confirmed" }, TOKEN_PROGRAM_ID);
+    mint_y = await createMint(connection, initializer, initializer.publicKey, null, 6, undefined, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+    initializer_x_ata = await createAssociatedTokenAccount(connection, initializer, mint_x, initializer.publicKey, { commitment: "confirmed" }, TOKEN_PROGRAM_ID);
+    initializer_y_ata = await createAssociatedTokenAccount(connection, initializer, mint_y, initializer.publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+    await mintTo(connection, initializer, mint_x, initializer_x_ata, initializer, 1e12, [], { commitment: "confirmed" }, TOKEN_PROGRAM_ID).then(confirmTx);
+    await mintTo(connection, initializer, mint_y, initializer_y_ata, initializer, 1e12, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID).then(confirmTx);
+    pool = fairswapPdas(program.programId, mint_x, mint_y, seed);
+    initializer_lp_ata = getAssociatedTokenAddressSync(pool.mintLp, initializer.publicKey, false, TOKEN_PROGRAM_ID);
+
+    const tx = await program.methods.initialize(seed, 30, 0, new BN(1), initializer.publicKey, { lp: {} }, { constantProduct: {} }, new BN(0), 50, 50)
+      .accountsPartial({
+        admin: initializer.publicKey,
+        auth: pool.auth,
+        mintX: mint_x,
+        mintY: mint_y,
+        vaultX: pool.vaultX,
+        vaultY: pool.vaultY,
+        config: pool.config,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    expect((await connection.getAccountInfo(pool.vaultX)).owner.equals(TOKEN_PROGRAM_ID)).to.be.true;
+    expect((await connection.getAccountInfo(pool.vaultY)).owner.equals(TOKEN_2022_PROGRAM_ID)).to.be.true;
+  });
+
+  it("Deposit", async () => {
+    // sqrt(1e8 * 1e8) less the locked minimum liquidity
+    const tx = await program.methods.deposit(new BN(1e8 - 1000), new BN(1e8), new BN(1e8), deadlineIn(600))
+      .accountsStrict(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    expect((await connection.getAccountInfo(pool.mintLp)).owner.equals(TOKEN_PROGRAM_ID)).to.be.true;
+    expect((await balance(initializer_lp_ata, TOKEN_PROGRAM_ID)).toNumber()).to.equal(1e8 - 1000);
+  });
+
+  it("Swap X for Y and back", async () => {
+    const yBefore = await balance(initializer_y_ata, TOKEN_2022_PROGRAM_ID);
+    const tx = await program.methods.swap(mint_x, new BN(1e6), new BN(9e5), deadlineIn(600))
+      .accountsPartial(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+    const received = (await balance(initializer_y_ata, TOKEN_2022_PROGRAM_ID)).sub(yBefore);
+    expect(received.gte(new BN(9e5))).to.be.true;
+
+    const tx2 = await program.methods.swap(mint_y, received, new BN(9e5), deadlineIn(600))
+      .accountsPartial(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx2);
+  });
+
+  it("Withdraw", async () => {
+    const tx = await program.methods.withdraw(new BN(5e7), new BN(4e7), new BN(4e7), deadlineIn(600))
+      .accountsStrict(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+    expect((await balance(initializer_lp_ata, TOKEN_PROGRAM_ID)).toNumber()).to.equal(5e7 - 1000);
+  });
+});
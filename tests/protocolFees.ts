@@ -0,0 +1,134 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Fairswap } from "../target/types/fairswap";
+
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js"
+import { TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync } from "@solana/spl-token"
+import { randomBytes } from "crypto"
+import { expect } from "chai"
+import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
+import { confirmTx, fairswapPdas, newMintToAta, deadlineIn } from "./utils";
+
+describe("fairswap protocol fees", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Fairswap as Program<Fairswap>;
+  const connection = anchor.getProvider().connection;
+  const initializer = new Keypair();
+  const treasury = new Keypair();
+  const outsider = new Keypair();
+  const seed = new BN(randomBytes(8));
+
+  let mint_x: PublicKey;
+  let mint_y: PublicKey;
+  let initializer_x_ata: PublicKey;
+  let initializer_y_ata: PublicKey;
+  let pool: ReturnType<typeof fairswapPdas>;
+
+  const poolAccounts = () => ({
+    user: initializer.publicKey,
+    auth: pool.auth,
+    mintX: mint_x,
+    mintY: mint_y,
+    userAtaX: initializer_x_ata,
+    userAtaY: initializer_y_ata,
+    userAtaLp: getAssociatedTokenAddressSync(pool.mintLp, initializer.publicKey),
+    lockedLp: pool.lockedLp,
+    vaultX: pool.vaultX,
+    vaultY: pool.vaultY,
+    mintLp: pool.mintLp,
+    config: pool.config,
+    pooldata: pool.pooldata,
+    tokenProgramX: TOKEN_PROGRAM_ID,
+    tokenProgramY: TOKEN_PROGRAM_ID,
+    tokenProgramLp: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  const collectAccounts = (authority: PublicKey) => ({
+    authority,
+    treasury: treasury.publicKey,
+    mintX: mint_x,
+    mintY: mint_y,
+    auth: pool.auth,
+    treasuryAtaX: getAssociatedTokenAddressSync(mint_x, treasury.publicKey),
+    treasuryAtaY: getAssociatedTokenAddressSync(mint_y, treasury.publicKey),
+    vaultX: pool.vaultX,
+    vaultY: pool.vaultY,
+    config: pool.config,
+    pooldata: pool.pooldata,
+    tokenProgramX: TOKEN_PROGRAM_ID,
+    tokenProgramY: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Airdrop", async () => {
+    await Promise.all([initializer, outsider].map((k) =>
+      connection.requestAirdrop(k.publicKey, 100 * anchor.web3.LAMPORTS_PER_SOL).then(confirmTx)));
+  });
+
+  it("Initialize with half of a 1% fee going to the treasury", async () => {
+    const x = await newMintToAta(connection, initializer, BigInt(1e12));
+    const y = await newMintToAta(connection, initializer, BigInt(1e12));
+    [mint_x, initializer_x_ata] = [x.mint, x.ata];
+    [mint_y, initializer_y_ata] = [y.mint, y.ata];
+    pool = fairswapPdas(program.programId, mint_x, mint_y, seed);
+
+    const tx = await program.methods.initialize(seed, 100, 5000, new BN(1), treasury.publicKey, { lp: {} }, { constantProduct: {} }, new BN(0), 50, 50)
+      .accountsPartial({
+        admin: initializer.publicKey,
+        auth: pool.auth,
+        mintX: mint_x,
+        mintY: mint_y,
+        vaultX: pool.vaultX,
+        vaultY: pool.vaultY,
+        config: pool.config,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    const config = await program.account.config.fetch(pool.config);
+    expect(config.protocolFeeBps).to.equal(5000);
+  });
+
+  it("Deposit and swap X for Y", async () => {
+    await program.methods.deposit(new BN(1e8 - 1000), new BN(1e8), new BN(1e8), deadlineIn(600))
+      .accountsStrict(poolAccounts())
+      .signers([initializer]).rpc().then(confirmTx);
+    await program.methods.swap(mint_x, new BN(1e6), new BN(1), deadlineIn(600))
+      .accountsPartial(poolAccounts())
+      .signers([initializer]).rpc().then(confirmTx);
+
+    // 1% of 1e6 is charged, half of it is kept for the treasury
+    const pooldata = await program.account.poolData.fetch(pool.pooldata);
+    expect(pooldata.protocolFeesX.toNumber()).to.equal(5000);
+    expect(pooldata.protocolFeesY.toNumber()).to.equal(0);
+  });
+
+  it("Fail to collect protocol fees without the authority", async () => {
+    try {
+      await program.methods.collectProtocolFees()
+        .accountsStrict(collectAccounts(outsider.publicKey))
+        .signers([outsider]).rpc();
+      expect.fail("collect should have failed");
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal("Unauthorized");
+    }
+  });
+
+  it("Collect protocol fees to the treasury", async () => {
+    await program.methods.collectProtocolFees()
+      .accountsStrict(collectAccounts(initializer.publicKey))
+      .signers([initializer]).rpc().then(confirmTx);
+
+    const balance = await connection.getTokenAccountBalance(getAssociatedTokenAddressSync(mint_x, treasury.publicKey));
+    expect(balance.value.amount).to.equal("5000");
+    const pooldata = await program.account.poolData.fetch(pool.pooldata);
+    expect(pooldata.protocolFeesX.toNumber()).to.equal(0);
+  });
+});
@@ -0,0 +1,146 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Fairswap } from "../target/types/fairswap";
+
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js"
+import { TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, getAssociatedTokenAddress } from "@solana/spl-token"
+import { randomBytes } from "crypto"
+import { expect } from "chai"
+import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
+import { confirmTx, confirmTxs, createAndFundATA, fairswapPdas, newMintToAta, deadlineIn } from "./utils";
+
+describe("fairswap routed swaps", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Fairswap as Program<Fairswap>;
+  const [initializer, trader] = [new Keypair(), new Keypair()];
+  const seed = new BN(randomBytes(8));
+
+  let mint_a: PublicKey;
+  let mint_b: PublicKey;
+  let mint_c: PublicKey;
+  let initializer_atas: PublicKey[];
+  let trader_atas: PublicKey[];
+  let pool_ab: ReturnType<typeof fairswapPdas>;
+  let pool_bc: ReturnType<typeof fairswapPdas>;
+
+  const hopAccounts = (pool: ReturnType<typeof fairswapPdas>, mintX: PublicKey, mintY: PublicKey, userX: PublicKey, userY: PublicKey) => [
+    { pubkey: pool.config, isSigner: false, isWritable: false },
+    { pubkey: pool.pooldata, isSigner: false, isWritable: true },
+    { pubkey: pool.auth, isSigner: false, isWritable: false },
+    { pubkey: mintX, isSigner: false, isWritable: false },
+    { pubkey: mintY, isSigner: false, isWritable: false },
+    { pubkey: pool.vaultX, isSigner: false, isWritable: true },
+    { pubkey: pool.vaultY, isSigner: false, isWritable: true },
+    { pubkey: userX, isSigner: false, isWritable: true },
+    { pubkey: userY, isSigner: false, isWritable: true },
+  ];
+
+  it("Airdrop", async () => {
+    await Promise.all([initializer, trader].map(async (k) => {
+      return await anchor.getProvider().connection.requestAirdrop(k.publicKey, 100 * anchor.web3.LAMPORTS_PER_SOL)
+    })).then(confirmTxs);
+  });
+
+  it("Create mints and two pools sharing a mint", async () => {
+    const connection = anchor.getProvider().connection;
+    const [a, b, c] = await Promise.all([0, 1, 2].map(async () => await newMintToAta(connection, initializer, 1e9)));
+    [mint_a, mint_b, mint_c] = [a.mint, b.mint, c.mint];
+    initializer_atas = [a.ata, b.ata, c.ata];
+    trader_atas = [
+      await createAndFundATA(connection, initializer, mint_a, trader.publicKey, 100000),
+      await createAndFundATA(connection, initializer, mint_b, trader.publicKey, 0),
+      await createAndFundATA(connection, initializer, mint_c, trader.publicKey, 0),
+    ];
+
+    pool_ab = fairswapPdas(program.programId, mint_a, mint_b, seed);
+    pool_bc = fairswapPdas(program.programId, mint_b, mint_c, seed);
+
+    for (const [pool, mintX, mintY, ataX, ataY] of [
+      [pool_ab, mint_a, mint_b, initializer_atas[0], initializer_atas[1]],
+      [pool_bc, mint_b, mint_c, initializer_atas[1], initializer_atas[2]],
+    ] as [ReturnType<typeof fairswapPdas>, PublicKey, PublicKey, PublicKey, PublicKey][]) {
+      const tx = await program.methods.initialize(seed, 30, 0, new BN(1), initializer.publicKey, { lp: {} }, { constantProduct: {} }, new BN(0), 50, 50)
+        .accountsPartial({
+          admin: initializer.publicKey,
+          auth: pool.auth,
+          mintX,
+          mintY,
+          vaultX: pool.vaultX,
+          vaultY: pool.vaultY,
+          config: pool.config,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([initializer]).rpc();
+      await confirmTx(tx);
+
+      const tx2 = await program.methods.deposit(new BN(1e6 - 1000), new BN(1e6), new BN(1e6), deadlineIn(600))
+        .accountsStrict({
+          user: initializer.publicKey,
+          auth: pool.auth,
+          mintX,
+          mintY,
+          userAtaX: ataX,
+          userAtaY: ataY,
+          userAtaLp: await getAssociatedTokenAddress(pool.mintLp, initializer.publicKey),
+          lockedLp: pool.lockedLp,
+          vaultX: pool.vaultX,
+          vaultY: pool.vaultY,
+          mintLp: pool.mintLp,
+          config: pool.config,
+          pooldata: pool.pooldata,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          tokenProgramLp: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([initializer]).rpc();
+      await confirmTx(tx2);
+    }
+  });
+
+  it("Route A -> B -> C", async () => {
+    const tx = await program.methods.routeSwap(mint_a, new BN(10000), new BN(9000), deadlineIn(600))
+      .accountsPartial({
+        user: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        ...hopAccounts(pool_ab, mint_a, mint_b, trader_atas[0], trader_atas[1]),
+        ...hopAccounts(pool_bc, mint_b, mint_c, trader_atas[1], trader_atas[2]),
+      ])
+      .signers([trader]).rpc();
+    await confirmTx(tx);
+    console.log("Your route transaction signature", tx);
+
+    const connection = anchor.getProvider().connection;
+    const [balanceA, balanceB, balanceC] = await Promise.all(trader_atas.map(async (ata) => (await connection.getTokenAccountBalance(ata)).value.amount));
+    expect(balanceA).to.equal("90000");
+    expect(balanceB).to.equal("0");
+    expect(Number(balanceC)).to.be.at.least(9000);
+  });
+
+  it("Fail route below end-to-end minimum", async () => {
+    try {
+      await program.methods.routeSwap(mint_a, new BN(10000), new BN(10000), deadlineIn(600))
+        .accountsPartial({
+          user: trader.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          ...hopAccounts(pool_ab, mint_a, mint_b, trader_atas[0], trader_atas[1]),
+          ...hopAccounts(pool_bc, mint_b, mint_c, trader_atas[1], trader_atas[2]),
+        ])
+        .signers([trader]).rpc();
+      expect.fail("Route should have failed on slippage");
+    } catch (e) {
+      let err = e as anchor.AnchorError;
+      expect(err.error.errorCode.code).to.equal("SlippageExceeded");
+    }
+  });
+});
@@ -0,0 +1,163 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Fairswap } from "../target/types/fairswap";
+
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js"
+import { TOKEN_2022_PROGRAM_ID, getAssociatedTokenAddressSync, getAccount, getMint } from "@solana/spl-token"
+import { randomBytes } from "crypto"
+import { expect } from "chai"
+import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
+import { confirmTx, fairswapPdas, newTransferFeeMintToAta, deadlineIn } from "./utils";
+
+// 1% on X, 2% on Y
+const FEE_BPS_X = 100;
+const FEE_BPS_Y = 200;
+
+describe("fairswap with transfer fee mints", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Fairswap as Program<Fairswap>;
+  const connection = anchor.getProvider().connection;
+  const initializer = new Keypair();
+  const seed = new BN(randomBytes(8));
+
+  let mint_x: PublicKey;
+  let mint_y: PublicKey;
+  let initializer_x_ata: PublicKey;
+  let initializer_y_ata: PublicKey;
+  let initializer_lp_ata: PublicKey;
+  let pool: ReturnType<typeof fairswapPdas>;
+
+  const balance = async (account: PublicKey) =>
+    new BN((await getAccount(connection, account, "confirmed", TOKEN_2022_PROGRAM_ID)).amount.toString());
+
+  const poolAccounts = () => ({
+    user: initializer.publicKey,
+    auth: pool.auth,
+    mintX: mint_x,
+    mintY: mint_y,
+    userAtaX: initializer_x_ata,
+    userAtaY: initializer_y_ata,
+    userAtaLp: initializer_lp_ata,
+    lockedLp: pool.lockedLp,
+    vaultX: pool.vaultX,
+    vaultY: pool.vaultY,
+    mintLp: pool.mintLp,
+    config: pool.config,
+    pooldata: pool.pooldata,
+    tokenProgramX: TOKEN_2022_PROGRAM_ID,
+    tokenProgramY: TOKEN_2022_PROGRAM_ID,
+    tokenProgramLp: TOKEN_2022_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    systemProgram: SystemProgram.programId
+  });
+
+  it("Airdrop", async () => {
+    await connection.requestAirdrop(initializer.publicKey, 100 * anchor.web3.LAMPORTS_PER_SOL).then(confirmTx);
+  });
+
+  it("Create mints and initialize", async () => {
+    const x = await newTransferFeeMintToAta(connection, initializer, BigInt(1e12), FEE_BPS_X, BigInt(1e12));
+    const y = await newTransferFeeMintToAta(connection, initializer, BigInt(1e12), FEE_BPS_Y, BigInt(1e12));
+    [mint_x, initializer_x_ata] = [x.mint, x.ata];
+    [mint_y, initializer_y_ata] = [y.mint, y.ata];
+    pool = fairswapPdas(program.programId, mint_x, mint_y, seed);
+    initializer_lp_ata = getAssociatedTokenAddressSync(pool.mintLp, initializer.publicKey, false, TOKEN_2022_PROGRAM_ID);
+
+    const tx = await program.methods.initialize(seed, 30, 0, new BN(1), initializer.publicKey, { lp: {} }, { constantProduct: {} }, new BN(0), 50, 50)
+      .accountsPartial({
+        admin: initializer.publicKey,
+        auth: pool.auth,
+        mintX: mint_x,
+        mintY: mint_y,
+        vaultX: pool.vaultX,
+        vaultY: pool.vaultY,
+        config: pool.config,
+        tokenProgramX: TOKEN_2022_PROGRAM_ID,
+        tokenProgramY: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+  });
+
+  it("First deposit sends max amounts, vaults hold them net of fees", async () => {
+    const tx = await program.methods.deposit(new BN(1), new BN(100_000_000), new BN(200_000_000), deadlineIn(600))
+      .accountsStrict(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    expect((await balance(pool.vaultX)).toNumber()).to.equal(99_000_000);
+    expect((await balance(pool.vaultY)).toNumber()).to.equal(196_000_000);
+  });
+
+  it("Deposit grosses up amounts so the ratio lands in the vaults", async () => {
+    const [vaultXBefore, vaultYBefore] = [await balance(pool.vaultX), await balance(pool.vaultY)];
+    const supply = new BN((await getMint(connection, pool.mintLp, "confirmed", TOKEN_2022_PROGRAM_ID)).supply.toString());
+    const amount = supply.divn(10);
+    const tx = await program.methods.deposit(amount, new BN(11_000_000), new BN(21_000_000), deadlineIn(600))
+      .accountsStrict(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    // what lands in each vault covers the minted share of its reserve
+    const landedX = (await balance(pool.vaultX)).sub(vaultXBefore);
+    const landedY = (await balance(pool.vaultY)).sub(vaultYBefore);
+    expect(landedX.mul(supply).gte(amount.mul(vaultXBefore))).to.be.true;
+    expect(landedY.mul(supply).gte(amount.mul(vaultYBefore))).to.be.true;
+  });
+
+  it("Quote matches what the swap delivers after both transfer fees", async () => {
+    const amountIn = new BN(1_000_000);
+    const quote = await program.methods.quote(mint_x, amountIn, new BN(1))
+      .accountsPartial(poolAccounts())
+      .view();
+
+    const before = await balance(initializer_y_ata);
+    const tx = await program.methods.swap(mint_x, amountIn, quote.amountOut, deadlineIn(600))
+      .accountsPartial(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+    const received = (await balance(initializer_y_ata)).sub(before);
+
+    expect(received.eq(quote.amountOut)).to.be.true;
+    expect(quote.amountOut.lt(quote.amountOutRaw)).to.be.true;
+  });
+
+  it("Fail to swap when the fee pushes output below the minimum", async () => {
+    const amountIn = new BN(1_000_000);
+    const quote = await program.methods.quote(mint_x, amountIn, new BN(1))
+      .accountsPartial(poolAccounts())
+      .view();
+    try {
+      await program.methods.swap(mint_x, amountIn, quote.amountOut.addn(1), deadlineIn(600))
+        .accountsPartial(poolAccounts())
+        .signers([initializer]).rpc();
+      expect.fail("swap should have failed");
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal("SlippageExceeded");
+    }
+  });
+
+  it("Swap Y for exactly 500000 X received", async () => {
+    const before = await balance(initializer_x_ata);
+    const tx = await program.methods.swapExactOut(mint_y, new BN(500_000), new BN(5_000_000), deadlineIn(600))
+      .accountsPartial(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    expect((await balance(initializer_x_ata)).sub(before).toNumber()).to.equal(500_000);
+  });
+
+  it("Withdraw checks minimums against amounts received", async () => {
+    const lp = await balance(initializer_lp_ata);
+    const [xBefore, yBefore] = [await balance(initializer_x_ata), await balance(initializer_y_ata)];
+    const tx = await program.methods.withdraw(lp.divn(2), new BN(50_000_000), new BN(100_000_000), deadlineIn(600))
+      .accountsStrict(poolAccounts())
+      .signers([initializer]).rpc();
+    await confirmTx(tx);
+
+    expect((await balance(initializer_x_ata)).sub(xBefore).gte(new BN(50_000_000))).to.be.true;
+    expect((await balance(initializer_y_ata)).sub(yBefore).gte(new BN(100_000_000))).to.be.true;
+  });
+});
@@ -1,8 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
 import { BN } from "@coral-xyz/anchor";
 
-import { createMint, createAccount, mintTo, getAssociatedTokenAddress, getAccount, createAssociatedTokenAccount } from "@solana/spl-token"
-import { PublicKey, Commitment, Keypair } from "@solana/web3.js"
+import { createMint, createAccount, mintTo, getAssociatedTokenAddress, getAccount, createAssociatedTokenAccount, ExtensionType, TOKEN_2022_PROGRAM_ID, getMintLen, createInitializeTransferFeeConfigInstruction, createInitializeMintInstruction } from "@solana/spl-token"
+import { PublicKey, Commitment, Keypair, SystemProgram, Transaction, sendAndConfirmTransaction } from "@solana/web3.js"
 
 const commitment: Commitment = "confirmed";
 
@@ -22,8 +22,8 @@ export const confirmTxs = async (signatures: string[]) => {
     await Promise.all(signatures.map(confirmTx));
 }
 
-export const newMintToAta = async (connection, minter: Keypair, amount): Promise<{ mint: PublicKey, ata: PublicKey }> => {
-    const mint = await createMint(connection, minter, minter.publicKey, null, 6);
+export const newMintToAta = async (connection, minter: Keypair, amount, decimals: number = 6): Promise<{ mint: PublicKey, ata: PublicKey }> => {
+    const mint = await createMint(connection, minter, minter.publicKey, null, decimals);
     // await getAccount(connection, mint, commitment)
     const ata = await createAccount(connection, minter, mint, minter.publicKey);
     const signature = await mintTo(connection, minter, mint, ata, minter, amount);
@@ -34,6 +34,32 @@ export const newMintToAta = async (connection, minter: Keypair, amount): Promise
     }
 }
 
+// Token-2022 mint withholding feeBps of every transfer, capped at maxFee
+export const newTransferFeeMintToAta = async (connection, minter: Keypair, amount, feeBps: number, maxFee: bigint, decimals: number = 6): Promise<{ mint: PublicKey, ata: PublicKey }> => {
+    const mint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await connection.getMinimumBalanceForRentExemption(mintLen);
+    const tx = new Transaction().add(
+        SystemProgram.createAccount({
+            fromPubkey: minter.publicKey,
+            newAccountPubkey: mint.publicKey,
+            space: mintLen,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(mint.publicKey, minter.publicKey, minter.publicKey, feeBps, maxFee, TOKEN_2022_PROGRAM_ID),
+        createInitializeMintInstruction(mint.publicKey, decimals, minter.publicKey, null, TOKEN_2022_PROGRAM_ID),
+    );
+    await sendAndConfirmTransaction(connection, tx, [minter, mint], { commitment });
+    const ata = await createAssociatedTokenAccount(connection, minter, mint.publicKey, minter.publicKey, { commitment }, TOKEN_2022_PROGRAM_ID);
+    const signature = await mintTo(connection, minter, mint.publicKey, ata, minter, amount, [], { commitment }, TOKEN_2022_PROGRAM_ID);
+    await confirmTx(signature);
+    return {
+        mint: mint.publicKey,
+        ata
+    }
+}
+
 export async function fetchTokenBalances(
     connection: anchor.web3.Connection,
     userPublicKey: PublicKey,
@@ -140,4 +166,29 @@ export async function buildTxConfirmOrLog(signer:anchor.web3.Keypair, ix: anchor
         }
         throw new Error(`Transaction failed: ${JSON.stringify(txDetails.meta.err)}`);
     }   
-}
\ No newline at end of file
+}
+export function fairswapPdas(programId: PublicKey, mintX: PublicKey, mintY: PublicKey, seed: BN) {
+    const config = PublicKey.findProgramAddressSync([Buffer.from("config"), mintX.toBuffer(), mintY.toBuffer(), seed.toArrayLike(Buffer, "le", 8)], programId)[0];
+    return {
+        config,
+        pooldata: PublicKey.findProgramAddressSync([Buffer.from("pooldata"), config.toBuffer()], programId)[0],
+        auth: PublicKey.findProgramAddressSync([Buffer.from("auth"), config.toBuffer()], programId)[0],
+        mintLp: PublicKey.findProgramAddressSync([Buffer.from("mint_lp"), config.toBuffer()], programId)[0],
+        lockedLp: PublicKey.findProgramAddressSync([Buffer.from("locked_lp"), config.toBuffer()], programId)[0],
+        vaultX: PublicKey.findProgramAddressSync([Buffer.from("vault"), config.toBuffer(), mintX.toBuffer()], programId)[0],
+        vaultY: PublicKey.findProgramAddressSync([Buffer.from("vault"), config.toBuffer(), mintY.toBuffer()], programId)[0],
+    }
+}
+
+// Events emitted by a confirmed transaction, decoded with the program's IDL
+export async function fetchEvents(program: anchor.Program<any>, signature: string) {
+    const tx = await program.provider.connection.getTransaction(signature, {
+        maxSupportedTransactionVersion: 0,
+        commitment: "confirmed"
+    });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return Array.from(parser.parseLogs(tx?.meta?.logMessages ?? []));
+}
+
+// fairswap Deadline that expires the given number of seconds from now
+export const deadlineIn = (seconds: number) => ({ timestamp: [new BN(Math.floor(Date.now() / 1000) + seconds)] });